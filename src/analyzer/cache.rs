@@ -52,6 +52,12 @@ impl CacheKey {
         })
     }
 
+    /// Marks the cache stale without waiting for the next verification, e.g.
+    /// when the file is known to have changed on disk.
+    pub fn invalidate(&self) {
+        *self.state.write().unwrap() = CacheState::Stale;
+    }
+
     pub fn verify(&self, request_time: Instant, storage: &DocumentStorage) -> bool {
         // Fast path with a read lock.
         let expires = match &*self.state.read().unwrap() {
@@ -72,7 +78,15 @@ impl CacheKey {
             return true;
         }
 
-        let version = storage.read_version(&self.path);
+        // Files on disk are not checked if the client notifies their changes.
+        let version = if !matches!(self.version, DocumentVersion::InMemory { .. })
+            && storage.is_watched(&self.path)
+            && !storage.is_in_memory(&self.path)
+        {
+            self.version
+        } else {
+            storage.read_version(&self.path)
+        };
         if version != self.version {
             *state_guard = CacheState::Stale;
             return false;
@@ -84,3 +98,30 @@ impl CacheKey {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::SystemTime};
+
+    use super::*;
+
+    #[test]
+    fn verify_watched_files() {
+        let path = Path::new("/nonexistent/BUILD.gn");
+        let version = DocumentVersion::OnDisk {
+            modified: SystemTime::UNIX_EPOCH,
+        };
+        let t = Instant::now();
+        let later = t + VERIFY_INTERVAL * 2;
+
+        // Files are checked on disk after the interval.
+        let mut storage = DocumentStorage::new();
+        assert!(!CacheKey::new(path.to_path_buf(), version, t).verify(later, &storage));
+
+        // Watched files are not, unless they are opened.
+        storage.set_watched_roots(vec![PathBuf::from("/nonexistent")]);
+        assert!(CacheKey::new(path.to_path_buf(), version, t).verify(later, &storage));
+        storage.load_to_memory(path, "", 1);
+        assert!(!CacheKey::new(path.to_path_buf(), version, t).verify(later, &storage));
+    }
+}
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
     sync::Arc,
     time::Instant,
@@ -25,7 +25,12 @@ use self_cell::self_cell;
 use tower_lsp::lsp_types::Url;
 
 use crate::{
-    analyzer::{cache::CacheKey, toplevel::TopLevelStatementsExt, utils::resolve_path},
+    analyzer::{
+        cache::CacheKey,
+        parameters::{collect_template_parameters, is_invoker, TemplateParameter},
+        toplevel::TopLevelStatementsExt,
+        utils::resolve_path,
+    },
     common::{
         builtins::{FOREACH, FORWARD_VARIABLES_FROM},
         storage::{Document, DocumentVersion},
@@ -33,7 +38,8 @@ use crate::{
         workspace::find_nearest_workspace_root,
     },
     parser::{
-        Assignment, Call, Comments, Condition, ErrorStatement, Expr, Identifier, Node, OwnedBlock,
+        string_content_span, Assignment, Call, Comments, Condition, ErrorStatement, Expr,
+        Identifier, Node, OwnedBlock,
     },
 };
 
@@ -122,6 +128,10 @@ impl OwnedEnvironment {
     pub fn get(&self) -> &Environment<'_> {
         self.0.borrow_dependent()
     }
+
+    pub fn files(&self) -> &[Arc<AnalyzedFile>] {
+        self.0.borrow_owner()
+    }
 }
 
 pub struct AnalyzedFile {
@@ -131,6 +141,9 @@ pub struct AnalyzedFile {
     pub analyzed_root: OwnedAnalyzedBlock,
     pub exports: OwnedFileExports,
     pub link_index: OwnedLinkIndex,
+    /// Names of the functions invoked as targets, including templates, at any
+    /// depth. Used to find invocations of a template without reparsing files.
+    pub target_functions: HashSet<String>,
     pub external: bool,
     pub key: Arc<CacheKey>,
 }
@@ -149,6 +162,15 @@ impl AnalyzedFile {
         let external =
             find_nearest_workspace_root(&document.path).is_none_or(|path| path != workspace_root);
         let key = CacheKey::new(document.path.clone(), document.version, request_time);
+        let target_functions = analyzed_root
+            .get()
+            .all_statements()
+            .into_iter()
+            .filter_map(|statement| match statement {
+                AnalyzedStatement::Target(target) => Some(target.call.function.name.to_string()),
+                _ => None,
+            })
+            .collect();
         Self {
             document,
             workspace_root,
@@ -156,6 +178,7 @@ impl AnalyzedFile {
             analyzed_root,
             exports,
             link_index,
+            target_functions,
             external,
             key,
         }
@@ -206,10 +229,9 @@ impl<'p> AnalyzedBlock<'p> {
         })
     }
 
-    pub fn local_variables_at(&self, pos: usize) -> VariableMap<'p> {
+    /// Returns variables assigned in the scope, excluding subscopes.
+    pub fn variables(&self) -> VariableMap<'p> {
         let mut variables = VariableMap::new();
-
-        // First pass: Collect all variables in the scope.
         let mut declare_args_stack: Vec<&AnalyzedDeclareArgs> = Vec::new();
         for statement in self.top_level_statements() {
             while let Some(last_declare_args) = declare_args_stack.last() {
@@ -222,12 +244,9 @@ impl<'p> AnalyzedBlock<'p> {
                 AnalyzedStatement::Assignment(assignment) => {
                     let assignment = assignment.as_variable_assignment(self.document);
                     variables
-                        .entry(assignment.primary_variable.as_str())
+                        .entry(assignment.name)
                         .or_insert_with(|| {
-                            Variable::new(
-                                assignment.primary_variable.as_str(),
-                                !declare_args_stack.is_empty(),
-                            )
+                            Variable::new(assignment.name, !declare_args_stack.is_empty())
                         })
                         .assignments
                         .push(assignment);
@@ -235,25 +254,22 @@ impl<'p> AnalyzedBlock<'p> {
                 AnalyzedStatement::Foreach(foreach) => {
                     let assignment = foreach.as_variable_assignment(self.document);
                     variables
-                        .entry(assignment.primary_variable.as_str())
+                        .entry(assignment.name)
                         .or_insert_with(|| {
-                            Variable::new(
-                                assignment.primary_variable.as_str(),
-                                !declare_args_stack.is_empty(),
-                            )
+                            Variable::new(assignment.name, !declare_args_stack.is_empty())
                         })
                         .assignments
                         .push(assignment);
                 }
                 AnalyzedStatement::ForwardVariablesFrom(forward_variables_from) => {
-                    for assignment in forward_variables_from.as_variable_assignment(self.document) {
+                    // Excludes are resolved in the variables assigned so far.
+                    for assignment in
+                        forward_variables_from.as_variable_assignment(self.document, &variables)
+                    {
                         variables
-                            .entry(assignment.primary_variable.as_str())
+                            .entry(assignment.name)
                             .or_insert_with(|| {
-                                Variable::new(
-                                    assignment.primary_variable.as_str(),
-                                    !declare_args_stack.is_empty(),
-                                )
+                                Variable::new(assignment.name, !declare_args_stack.is_empty())
                             })
                             .assignments
                             .push(assignment);
@@ -270,6 +286,12 @@ impl<'p> AnalyzedBlock<'p> {
                 | AnalyzedStatement::Error(_) => {}
             }
         }
+        variables
+    }
+
+    pub fn local_variables_at(&self, pos: usize) -> VariableMap<'p> {
+        // First pass: Collect all variables in the scope.
+        let mut variables = self.variables();
 
         // Second pass: Find the subscope that contains the position, and merge
         // its variables.
@@ -319,6 +341,69 @@ impl<'p> AnalyzedBlock<'p> {
 
         templates
     }
+
+    /// Returns the innermost template whose body contains the position.
+    pub fn enclosing_template_at(&self, pos: usize) -> Option<&AnalyzedTemplate<'p>> {
+        for statement in self.top_level_statements() {
+            for scope in statement.subscopes() {
+                if scope.span.start() < pos && pos < scope.span.end() {
+                    if let Some(template) = scope.enclosing_template_at(pos) {
+                        return Some(template);
+                    }
+                    if let AnalyzedStatement::Template(template) = statement {
+                        return Some(template);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the subscope, possibly nested, of the given span.
+    pub fn find_scope(&self, span: Span) -> Option<&AnalyzedBlock<'p>> {
+        if self.span == span {
+            return Some(self);
+        }
+        self.top_level_statements()
+            .flat_map(|statement| statement.subscopes())
+            .filter(|scope| scope.span.start() <= span.start() && span.end() <= scope.span.end())
+            .find_map(|scope| scope.find_scope(span))
+    }
+
+    /// Returns all statements in the scope and its subscopes.
+    pub fn all_statements<'a>(&'a self) -> Vec<&'a AnalyzedStatement<'p>> {
+        let mut statements = Vec::new();
+        for statement in self.top_level_statements() {
+            statements.push(statement);
+            for scope in statement.subscopes() {
+                statements.extend(scope.all_statements());
+            }
+        }
+        statements
+    }
+
+    /// Records the parameters of the enclosing template on wildcard forwards
+    /// from the invoker. Nested templates have their own invoker and are
+    /// skipped.
+    pub fn set_wildcard_parameters(&mut self, parameters: &[TemplateParameter<'p>]) {
+        for statement in &mut self.statements {
+            statement.set_wildcard_parameters(parameters);
+        }
+    }
+}
+
+impl<'p> AnalyzedCondition<'p> {
+    fn set_wildcard_parameters(&mut self, parameters: &[TemplateParameter<'p>]) {
+        for scope in &mut self.expr_scopes {
+            scope.set_wildcard_parameters(parameters);
+        }
+        self.then_block.set_wildcard_parameters(parameters);
+        match &mut self.else_block {
+            Some(Either::Left(condition)) => condition.set_wildcard_parameters(parameters),
+            Some(Either::Right(block)) => block.set_wildcard_parameters(parameters),
+            None => {}
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -407,6 +492,48 @@ impl<'p> AnalyzedStatement<'p> {
     pub fn subscopes(&self) -> impl Iterator<Item = &AnalyzedBlock<'p>> {
         self.body_scope().into_iter().chain(self.expr_scopes())
     }
+
+    fn set_wildcard_parameters(&mut self, parameters: &[TemplateParameter<'p>]) {
+        let scopes = match self {
+            AnalyzedStatement::Assignment(assignment) => &mut assignment.expr_scopes,
+            AnalyzedStatement::Conditions(condition) => {
+                condition.set_wildcard_parameters(parameters);
+                return;
+            }
+            AnalyzedStatement::DeclareArgs(declare_args) => {
+                declare_args.body_block.set_wildcard_parameters(parameters);
+                return;
+            }
+            AnalyzedStatement::Foreach(foreach) => {
+                foreach.body_block.set_wildcard_parameters(parameters);
+                &mut foreach.expr_scopes
+            }
+            AnalyzedStatement::ForwardVariablesFrom(forward_variables_from) => {
+                if forward_variables_from.is_wildcard()
+                    && is_invoker(&forward_variables_from.call.args[0])
+                {
+                    forward_variables_from.wildcard_parameters = parameters.to_vec();
+                }
+                &mut forward_variables_from.expr_scopes
+            }
+            AnalyzedStatement::Target(target) => {
+                target.body_block.set_wildcard_parameters(parameters);
+                &mut target.expr_scopes
+            }
+            AnalyzedStatement::BuiltinCall(builtin_call) => {
+                if let Some(body_block) = &mut builtin_call.body_block {
+                    body_block.set_wildcard_parameters(parameters);
+                }
+                &mut builtin_call.expr_scopes
+            }
+            AnalyzedStatement::Import(_)
+            | AnalyzedStatement::Template(_)
+            | AnalyzedStatement::Error(_) => return,
+        };
+        for scope in scopes {
+            scope.set_wildcard_parameters(parameters);
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -444,7 +571,11 @@ pub struct AnalyzedForeach<'p> {
 pub struct AnalyzedForwardVariablesFrom<'p> {
     pub call: &'p Call<'p>,
     pub includes: &'p Expr<'p>,
+    pub excludes: Option<&'p Expr<'p>>,
     pub expr_scopes: Vec<AnalyzedBlock<'p>>,
+    /// Parameters of the enclosing template, forwarded by
+    /// `forward_variables_from(invoker, "*")`.
+    pub wildcard_parameters: Vec<TemplateParameter<'p>>,
 }
 
 #[derive(Clone)]
@@ -504,7 +635,11 @@ pub struct Template<'p> {
     pub comments: Comments<'p>,
 }
 
-impl Template<'_> {
+impl<'p> Template<'p> {
+    pub fn parameters(&self) -> Vec<TemplateParameter<'p>> {
+        collect_template_parameters(self.call)
+    }
+
     pub fn format_help(&self, workspace_root: &Path) -> Vec<String> {
         let mut paragraphs = vec![format!("```gn\ntemplate(\"{}\") {{ ... }}\n```", self.name)];
         if !self.comments.is_empty() {
@@ -513,6 +648,22 @@ impl Template<'_> {
                 self.comments.to_string().trim()
             ));
         };
+        let parameters = self.parameters();
+        if !parameters.is_empty() {
+            let lines: Vec<String> = parameters
+                .iter()
+                .map(|parameter| {
+                    if parameter.required {
+                        format!("- `{}` (required)", parameter.name)
+                    } else if parameter.read_unconditionally {
+                        format!("- `{}` (read unconditionally)", parameter.name)
+                    } else {
+                        format!("- `{}`", parameter.name)
+                    }
+                })
+                .collect();
+            paragraphs.push(format!("Parameters:\n{}", lines.join("\n")));
+        }
         let position = self
             .document
             .line_index
@@ -548,6 +699,8 @@ pub struct Variable<'p> {
     pub name: &'p str,
     pub assignments: Vec<VariableAssignment<'p>>,
     pub is_args: bool,
+    /// Assignment in `args.gn` of the active build directory.
+    pub args_override: Option<VariableAssignment<'p>>,
 }
 
 impl<'p> Variable<'p> {
@@ -556,6 +709,7 @@ impl<'p> Variable<'p> {
             name,
             assignments: Vec::new(),
             is_args,
+            args_override: None,
         }
     }
 
@@ -589,7 +743,7 @@ impl<'p> Variable<'p> {
                 }
             }
         } else {
-            format!("{} = ...", first_assignment.primary_variable.as_str())
+            format!("{} = ...", first_assignment.name)
         };
 
         let mut paragraphs = vec![format!("```gn\n{snippet}\n```")];
@@ -623,6 +777,27 @@ impl<'p> Variable<'p> {
             )
         });
 
+        if let Some(args_override) = &self.args_override {
+            let snippet = match args_override.assignment_or_call {
+                Either::Left(assignment) => assignment.span.as_str(),
+                Either::Right(call) => call.span.as_str(),
+            };
+            let position = args_override
+                .document
+                .line_index
+                .position(args_override.primary_variable.start());
+            paragraphs.push(format!(
+                "Set in [{}:{}:{}]({}#L{},{}) of the active build directory:\n```gn\n{}\n```",
+                format_path(&args_override.document.path, workspace_root),
+                position.line + 1,
+                position.character + 1,
+                Url::from_file_path(&args_override.document.path).unwrap(),
+                position.line + 1,
+                position.character + 1,
+                snippet,
+            ));
+        }
+
         paragraphs
     }
 }
@@ -631,6 +806,8 @@ impl<'p> Variable<'p> {
 pub struct VariableAssignment<'p> {
     pub document: &'p Document,
     pub assignment_or_call: Either<&'p Assignment<'p>, &'p Call<'p>>,
+    pub name: &'p str,
+    /// Span within the assignment or call that names the variable.
     pub primary_variable: Span<'p>,
     pub comments: Comments<'p>,
 }
@@ -640,6 +817,7 @@ impl<'p> AnalyzedAssignment<'p> {
         VariableAssignment {
             document,
             assignment_or_call: Either::Left(self.assignment),
+            name: self.primary_variable.as_str(),
             primary_variable: self.primary_variable,
             comments: self.comments.clone(),
         }
@@ -651,6 +829,7 @@ impl<'p> AnalyzedForeach<'p> {
         VariableAssignment {
             document,
             assignment_or_call: Either::Right(self.call),
+            name: self.loop_variable.name,
             primary_variable: self.loop_variable.span,
             comments: Default::default(),
         }
@@ -658,38 +837,100 @@ impl<'p> AnalyzedForeach<'p> {
 }
 
 impl<'p> AnalyzedForwardVariablesFrom<'p> {
-    pub fn as_variable_assignment(&self, document: &'p Document) -> Vec<VariableAssignment<'p>> {
-        // TODO: Handle excludes.
-        let Some(strings) = self.includes.as_primary_list().map(|list| {
+    /// Returns whether all variables of the source scope are forwarded.
+    pub fn is_wildcard(&self) -> bool {
+        self.includes.as_simple_string() == Some("*")
+    }
+
+    /// Returns the names that are never forwarded. An identifier is resolved
+    /// to the literal lists assigned to it in `variables`.
+    pub fn excluded_names<'a>(&self, variables: &VariableMap<'a>) -> Vec<&'a str>
+    where
+        'p: 'a,
+    {
+        self.excludes
+            .map_or_else(Vec::new, |excludes| resolve_excludes(excludes, variables))
+    }
+
+    /// Returns the variables assigned by the call, resolving excludes in
+    /// `variables`.
+    pub fn as_variable_assignment(
+        &self,
+        document: &'p Document,
+        variables: &VariableMap<'p>,
+    ) -> Vec<VariableAssignment<'p>> {
+        let excluded_names = self.excluded_names(variables);
+        let assignments: Vec<(&'p str, Span<'p>)> = if self.is_wildcard() {
+            // Forwarded names are spelled in the template body, so point at
+            // the "*" in the call.
+            let span = string_content_span(self.includes.span());
+            self.wildcard_parameters
+                .iter()
+                .map(|parameter| (parameter.name, span))
+                .collect()
+        } else if let Some(list) = self.includes.as_primary_list() {
             list.values
                 .iter()
                 .filter_map(|expr| expr.as_primary_string())
-                .collect::<Vec<_>>()
-        }) else {
-            return Vec::new();
+                .filter(|string| parse_simple_literal(string.raw_value).is_some())
+                .map(|string| {
+                    let span = string_content_span(string.span);
+                    (span.as_str(), span)
+                })
+                .collect()
+        } else {
+            Vec::new()
         };
-        strings
+        assignments
             .into_iter()
-            .filter_map(|string| {
-                parse_simple_literal(string.raw_value).map(|_| {
-                    let primary_variable = Span::new(
-                        string.span.get_input(),
-                        string.span.start() + 1,
-                        string.span.end() - 1,
-                    )
-                    .unwrap();
-                    VariableAssignment {
-                        document,
-                        assignment_or_call: Either::Right(self.call),
-                        primary_variable,
-                        comments: Default::default(),
-                    }
-                })
+            .filter(|(name, _)| !excluded_names.contains(name))
+            .map(|(name, primary_variable)| VariableAssignment {
+                document,
+                assignment_or_call: Either::Right(self.call),
+                name,
+                primary_variable,
+                comments: Default::default(),
             })
             .collect()
     }
 }
 
+impl<'p> VariableAssignment<'p> {
+    /// Returns whether the assignment is a `forward_variables_from` call that
+    /// excludes the variable, resolving excludes in `variables`.
+    pub fn is_excluded_by(&self, variables: &VariableMap<'_>) -> bool {
+        let Either::Right(call) = self.assignment_or_call else {
+            return false;
+        };
+        call.function.name == FORWARD_VARIABLES_FROM
+            && call
+                .args
+                .get(2)
+                .is_some_and(|excludes| resolve_excludes(excludes, variables).contains(&self.name))
+    }
+}
+
+/// Returns the names in the excludes of a `forward_variables_from` call. An
+/// identifier is resolved to the literal lists assigned to it in `variables`.
+fn resolve_excludes<'a>(excludes: &Expr<'a>, variables: &VariableMap<'a>) -> Vec<&'a str> {
+    if let Some(names) = excludes.as_simple_string_list() {
+        return names;
+    }
+    let Some(identifier) = excludes.as_primary_identifier() else {
+        return Vec::new();
+    };
+    variables
+        .get(identifier.name)
+        .into_iter()
+        .flat_map(|variable| &variable.assignments)
+        .filter_map(|assignment| match assignment.assignment_or_call {
+            Either::Left(assignment) => assignment.rvalue.as_simple_string_list(),
+            Either::Right(_) => None,
+        })
+        .flatten()
+        .collect()
+}
+
 #[derive(Clone, Eq, PartialEq)]
 pub enum AnalyzedLink<'p> {
     /// Link to a file. No range is specified.
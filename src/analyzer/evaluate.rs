@@ -0,0 +1,100 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use crate::{
+    common::utils::parse_simple_literal,
+    parser::{BinaryOp, Expr, PrimaryExpr, UnaryOp},
+};
+
+/// Value of a build argument known without evaluating build files.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Bool(bool),
+    Integer(i64),
+    String(String),
+}
+
+pub type KnownValues = HashMap<String, Value>;
+
+/// Evaluates an expression statically, returning `None` if it depends on
+/// unknown variables or unsupported operations.
+pub fn evaluate(expr: &Expr, values: &KnownValues) -> Option<Value> {
+    match expr {
+        Expr::Primary(primary_expr) => match primary_expr.as_ref() {
+            PrimaryExpr::Identifier(identifier) => match identifier.name {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                name => values.get(name).cloned(),
+            },
+            PrimaryExpr::Integer(integer) => Some(Value::Integer(integer.value)),
+            PrimaryExpr::String(string) => {
+                parse_simple_literal(string.raw_value).map(|s| Value::String(s.to_string()))
+            }
+            PrimaryExpr::ParenExpr(paren_expr) => evaluate(&paren_expr.expr, values),
+            _ => None,
+        },
+        Expr::Unary(unary_expr) => match (unary_expr.op, evaluate(&unary_expr.expr, values)?) {
+            (UnaryOp::Not, Value::Bool(value)) => Some(Value::Bool(!value)),
+            _ => None,
+        },
+        Expr::Binary(binary_expr) => {
+            let lhs = evaluate(&binary_expr.lhs, values);
+            let rhs = evaluate(&binary_expr.rhs, values);
+            match binary_expr.op {
+                // Either side decides the result, as expressions have no side
+                // effects.
+                BinaryOp::And => match (lhs, rhs) {
+                    (Some(Value::Bool(false)), _) | (_, Some(Value::Bool(false))) => {
+                        Some(Value::Bool(false))
+                    }
+                    (Some(Value::Bool(true)), Some(Value::Bool(true))) => Some(Value::Bool(true)),
+                    _ => None,
+                },
+                BinaryOp::Or => match (lhs, rhs) {
+                    (Some(Value::Bool(true)), _) | (_, Some(Value::Bool(true))) => {
+                        Some(Value::Bool(true))
+                    }
+                    (Some(Value::Bool(false)), Some(Value::Bool(false))) => {
+                        Some(Value::Bool(false))
+                    }
+                    _ => None,
+                },
+                op => evaluate_binary(op, lhs?, rhs?),
+            }
+        }
+    }
+}
+
+fn evaluate_binary(op: BinaryOp, lhs: Value, rhs: Value) -> Option<Value> {
+    let value = match (op, lhs, rhs) {
+        (BinaryOp::Eq, lhs, rhs) => Value::Bool(lhs == rhs),
+        (BinaryOp::Ne, lhs, rhs) => Value::Bool(lhs != rhs),
+        (BinaryOp::Lt, Value::Integer(lhs), Value::Integer(rhs)) => Value::Bool(lhs < rhs),
+        (BinaryOp::Le, Value::Integer(lhs), Value::Integer(rhs)) => Value::Bool(lhs <= rhs),
+        (BinaryOp::Gt, Value::Integer(lhs), Value::Integer(rhs)) => Value::Bool(lhs > rhs),
+        (BinaryOp::Ge, Value::Integer(lhs), Value::Integer(rhs)) => Value::Bool(lhs >= rhs),
+        (BinaryOp::Add, Value::Integer(lhs), Value::Integer(rhs)) => {
+            Value::Integer(lhs.checked_add(rhs)?)
+        }
+        (BinaryOp::Sub, Value::Integer(lhs), Value::Integer(rhs)) => {
+            Value::Integer(lhs.checked_sub(rhs)?)
+        }
+        (BinaryOp::Add, Value::String(lhs), Value::String(rhs)) => Value::String(lhs + &rhs),
+        _ => return None,
+    };
+    Some(value)
+}
@@ -12,46 +12,100 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{sync::Arc, time::Instant};
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use futures::{future::join_all, FutureExt};
+use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
+use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{analyzer::WorkspaceAnalyzer, common::utils::find_gn_in_workspace_for_scan};
 
-pub async fn build_index(analyzer: &Arc<WorkspaceAnalyzer>, parallel: bool) {
-    eprintln!(
-        "Indexing {} in the background...",
-        analyzer.context().root.display()
-    );
+/// Minimum interval between progress reports of a workspace.
+const REPORT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Progress of background indexing of a workspace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IndexingProgress {
+    Begin {
+        root: PathBuf,
+    },
+    Report {
+        root: PathBuf,
+        processed: usize,
+        total: usize,
+    },
+    End {
+        root: PathBuf,
+        processed: usize,
+        elapsed: Duration,
+    },
+}
+
+pub type IndexingProgressSender = UnboundedSender<IndexingProgress>;
+
+pub async fn build_index(
+    analyzer: &Arc<WorkspaceAnalyzer>,
+    parallel: bool,
+    progress: Option<&IndexingProgressSender>,
+) {
+    let root = analyzer.context().root.clone();
+    let send_progress = |event: IndexingProgress| {
+        if let Some(progress) = progress {
+            // The receiver is gone only when the server is shutting down.
+            progress.send(event).ok();
+        }
+    };
+
+    eprintln!("Indexing {} in the background...", root.display());
+    send_progress(IndexingProgress::Begin { root: root.clone() });
 
     let start_time = Instant::now();
-    let mut tasks = Vec::new();
-    let mut count = 0;
+    let mut tasks = FuturesUnordered::new();
 
-    for path in find_gn_in_workspace_for_scan(&analyzer.context().root) {
+    for path in find_gn_in_workspace_for_scan(&root) {
         let analyzer = analyzer.clone();
         let task = async move {
             analyzer.analyze_file(&path, start_time);
         };
         let task = if parallel {
             async move {
-                tokio::spawn(task);
+                tokio::spawn(task).await.ok();
             }
             .boxed()
         } else {
             task.boxed()
         };
         tasks.push(task);
-        count += 1;
     }
 
-    join_all(tasks).await;
+    let total = tasks.len();
+    let mut processed = 0;
+    let mut last_report_time = start_time;
+    while tasks.next().await.is_some() {
+        processed += 1;
+        if last_report_time.elapsed() >= REPORT_INTERVAL {
+            last_report_time = Instant::now();
+            send_progress(IndexingProgress::Report {
+                root: root.clone(),
+                processed,
+                total,
+            });
+        }
+    }
 
     let elapsed = start_time.elapsed();
     eprintln!(
         "Finished indexing {}: processed {} files in {:.1}s",
-        analyzer.context().root.display(),
-        count,
+        root.display(),
+        processed,
         elapsed.as_secs_f64()
     );
+    send_progress(IndexingProgress::End {
+        root,
+        processed,
+        elapsed,
+    });
 }
@@ -14,6 +14,8 @@
 
 use std::path::{Path, PathBuf};
 
+use normalize_path::NormalizePath;
+
 use crate::{
     analyzer::{
         data::{AnalyzedLink, LinkIndex},
@@ -33,7 +35,12 @@ fn resolve_target<'s>(
         if let Some(rel_dir) = prefix.strip_prefix("//") {
             Some((workspace.root.join(rel_dir).join("BUILD.gn"), name))
         } else {
-            let build_path = current_path.parent().unwrap().join(prefix).join("BUILD.gn");
+            let build_path = current_path
+                .parent()
+                .unwrap()
+                .join(prefix)
+                .join("BUILD.gn")
+                .normalize();
             build_path.exists().then_some((build_path, name))
         }
     } else if let Some(rel_dir) = label.strip_prefix("//") {
@@ -14,6 +14,7 @@
 
 use std::{
     collections::{BTreeMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
     path::{Path, PathBuf},
     sync::{Arc, Mutex, RwLock},
     time::Instant,
@@ -22,20 +23,30 @@ use std::{
 use either::Either;
 
 use crate::{
-    analyzer::{dotgn::evaluate_dot_gn, indexing::build_index, links::collect_links},
+    analyzer::{
+        cache::CacheKey,
+        dotgn::evaluate_dot_gn,
+        indexing::build_index,
+        links::collect_links,
+        parameters::collect_template_parameters,
+        scope::{collect_invoker_members, collect_scope_members, read_file_scope_path},
+    },
     common::{
         builtins::{
-            DECLARE_ARGS, FOREACH, FORWARD_VARIABLES_FROM, IMPORT, SET_DEFAULTS, TEMPLATE,
+            DECLARE_ARGS, FOREACH, FORWARD_VARIABLES_FROM, IMPORT, INVOKER, SET_DEFAULTS, TEMPLATE,
             VISIBILITY,
         },
         error::{Error, Result},
         storage::{Document, DocumentStorage},
-        utils::{is_exported, is_good_for_scan, parse_simple_literal, AsyncSignal},
+        utils::{
+            find_output_dirs, is_exported, is_good_for_scan, is_in_source_dirs,
+            parse_simple_literal, AsyncSignal,
+        },
         workspace::WorkspaceFinder,
     },
     parser::{
-        parse, Block, Call, Comments, Condition, Expr, LValue, Node, OwnedBlock, PrimaryExpr,
-        Statement,
+        parse, AssignOp, Block, Call, Comments, Condition, Expr, LValue, Node, OwnedBlock,
+        PrimaryExpr, Statement,
     },
 };
 
@@ -44,16 +55,22 @@ pub use data::{
     AnalyzedFile, AnalyzedForeach, AnalyzedForwardVariablesFrom, AnalyzedImport, AnalyzedLink,
     AnalyzedStatement, AnalyzedTarget, AnalyzedTemplate, Environment, FileExports,
     OwnedAnalyzedBlock, OwnedEnvironment, OwnedFileExports, OwnedLinkIndex, Target, Template,
-    Variable, VariableAssignment, WorkspaceContext,
+    TemplateMap, Variable, VariableAssignment, VariableMap, WorkspaceContext,
 };
 
+pub use evaluate::{evaluate, KnownValues, Value};
+pub use indexing::{IndexingProgress, IndexingProgressSender};
+pub use parameters::TemplateParameter;
 pub use toplevel::TopLevelStatementsExt;
 
 mod cache;
 mod data;
 mod dotgn;
+mod evaluate;
 mod indexing;
 mod links;
+mod parameters;
+mod scope;
 mod tests;
 mod toplevel;
 mod utils;
@@ -68,6 +85,8 @@ pub struct Analyzer {
     storage: Arc<Mutex<DocumentStorage>>,
     workspace_finder: WorkspaceFinder,
     indexing_level: IndexingLevel,
+    indexing_progress: Option<IndexingProgressSender>,
+    build_dir_setting: RwLock<Option<PathBuf>>,
     workspaces: RwLock<BTreeMap<PathBuf, Arc<WorkspaceAnalyzer>>>,
 }
 
@@ -76,11 +95,14 @@ impl Analyzer {
         storage: &Arc<Mutex<DocumentStorage>>,
         workspace_finder: WorkspaceFinder,
         indexing_level: IndexingLevel,
+        indexing_progress: Option<IndexingProgressSender>,
     ) -> Self {
         Self {
             storage: storage.clone(),
             workspace_finder,
             indexing_level,
+            indexing_progress,
+            build_dir_setting: Default::default(),
             workspaces: Default::default(),
         }
     }
@@ -100,6 +122,12 @@ impl Analyzer {
             .analyze_at(file, pos, request_time))
     }
 
+    pub fn environment_key(&self, file: &Arc<AnalyzedFile>, request_time: Instant) -> Result<u64> {
+        Ok(self
+            .workspace_for(&file.document.path)?
+            .environment_key(file, request_time))
+    }
+
     pub fn workspaces(&self) -> BTreeMap<PathBuf, Arc<WorkspaceAnalyzer>> {
         self.workspaces.read().unwrap().clone()
     }
@@ -108,6 +136,63 @@ impl Analyzer {
         &self.workspace_finder
     }
 
+    /// Adds a folder opened by the client and starts analyzing the workspace
+    /// containing it.
+    pub fn add_client_root(&self, client_root: &Path) {
+        self.workspace_finder.add_client_root(client_root);
+        self.workspace_for(client_root).ok();
+    }
+
+    /// Removes a folder opened by the client and drops the workspaces in or
+    /// around it that no other folder uses.
+    pub fn remove_client_root(&self, client_root: &Path) {
+        self.workspace_finder.remove_client_root(client_root);
+        let related = |a: &Path, b: &Path| a.starts_with(b) || b.starts_with(a);
+        let client_roots = self.workspace_finder.client_roots();
+        self.workspaces.write().unwrap().retain(|root, _| {
+            !related(root, client_root)
+                || client_roots
+                    .iter()
+                    .any(|other_root| related(root, other_root))
+        });
+    }
+
+    /// Sets the active build directory of all workspaces from the
+    /// configuration, which may be relative to workspace roots. Directories
+    /// selected for individual workspaces are kept unless the configuration
+    /// has changed.
+    pub fn set_build_dir_setting(&self, build_dir: Option<PathBuf>) {
+        {
+            let mut setting = self.build_dir_setting.write().unwrap();
+            if *setting == build_dir {
+                return;
+            }
+            *setting = build_dir;
+        }
+        for workspace in self.workspaces().into_values() {
+            workspace.set_active_build_dir(self.configured_build_dir(&workspace.context().root));
+        }
+    }
+
+    fn configured_build_dir(&self, workspace_root: &Path) -> Option<PathBuf> {
+        self.build_dir_setting
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|build_dir| workspace_root.join(build_dir))
+    }
+
+    /// Updates the caches of the workspaces containing a file that changed on
+    /// disk.
+    pub fn file_changed(&self, path: &Path, request_time: Instant) {
+        let index = self.indexing_level != IndexingLevel::Disabled;
+        for (root, workspace) in self.workspaces() {
+            if path.starts_with(&root) {
+                workspace.file_changed(path, index, request_time);
+            }
+        }
+    }
+
     pub fn workspace_for(&self, path: &Path) -> Result<Arc<WorkspaceAnalyzer>> {
         if !path.is_absolute() {
             return Err(Error::General("Path must be absolute".to_string()));
@@ -145,6 +230,7 @@ impl Analyzer {
         };
 
         let analyzer = Arc::new(WorkspaceAnalyzer::new(&context, &self.storage));
+        analyzer.set_active_build_dir(self.configured_build_dir(workspace_root));
 
         match self.indexing_level {
             IndexingLevel::Disabled => {
@@ -152,8 +238,9 @@ impl Analyzer {
             }
             IndexingLevel::Enabled { parallel } => {
                 let analyzer = analyzer.clone();
+                let progress = self.indexing_progress.clone();
                 tokio::spawn(async move {
-                    build_index(&analyzer, parallel).await;
+                    build_index(&analyzer, parallel, progress.as_ref()).await;
                     analyzer.indexed().set();
                 });
             }
@@ -171,16 +258,31 @@ pub struct WorkspaceAnalyzer {
     context: WorkspaceContext,
     storage: Arc<Mutex<DocumentStorage>>,
     indexed: AsyncSignal,
+    active_build_dir: RwLock<Option<PathBuf>>,
+    args_files: RwLock<Option<ArgsFiles>>,
+    /// Cached result of `find_output_dirs`, reset when an `args.gn` changes.
+    output_dirs: RwLock<Option<Vec<PathBuf>>>,
     #[allow(clippy::type_complexity)]
     cache: RwLock<BTreeMap<PathBuf, Arc<Mutex<Option<Arc<AnalyzedFile>>>>>>,
 }
 
+/// Cached `args.gn` of a build directory and the files it imports.
+struct ArgsFiles {
+    build_dir: PathBuf,
+    /// Detects `args.gn` being created or removed.
+    key: Arc<CacheKey>,
+    files: Vec<Arc<AnalyzedFile>>,
+}
+
 impl WorkspaceAnalyzer {
     pub fn new(context: &WorkspaceContext, storage: &Arc<Mutex<DocumentStorage>>) -> Self {
         Self {
             context: context.clone(),
             storage: storage.clone(),
             indexed: AsyncSignal::new(),
+            active_build_dir: Default::default(),
+            args_files: Default::default(),
+            output_dirs: Default::default(),
             cache: Default::default(),
         }
     }
@@ -193,8 +295,38 @@ impl WorkspaceAnalyzer {
         &self.indexed
     }
 
+    /// Returns the build directory whose `args.gn` overrides the defaults of
+    /// build arguments.
+    pub fn active_build_dir(&self) -> Option<PathBuf> {
+        self.active_build_dir.read().unwrap().clone()
+    }
+
+    pub fn set_active_build_dir(&self, build_dir: Option<PathBuf>) {
+        *self.active_build_dir.write().unwrap() = build_dir;
+        *self.args_files.write().unwrap() = None;
+    }
+
+    /// Returns the build directories of the workspace. The tree is walked only
+    /// once while files are watched, as `file_changed` then reports new
+    /// `args.gn`.
+    pub fn output_dirs(&self) -> Vec<PathBuf> {
+        if !self.storage.lock().unwrap().is_watching() {
+            return find_output_dirs(&self.context.root);
+        }
+        let mut output_dirs = self.output_dirs.write().unwrap();
+        let output_dirs = output_dirs.get_or_insert_with(|| find_output_dirs(&self.context.root));
+        // Directories may be deleted as a whole.
+        output_dirs.retain(|dir| dir.join("args.gn").exists());
+        output_dirs.clone()
+    }
+
     pub async fn scan_files(&self) -> Vec<Arc<AnalyzedFile>> {
         self.indexed.wait().await;
+        self.cached_files()
+    }
+
+    /// Returns the files analyzed so far without waiting for indexing.
+    pub fn cached_files(&self) -> Vec<Arc<AnalyzedFile>> {
         self.cache
             .read()
             .unwrap()
@@ -232,6 +364,28 @@ impl WorkspaceAnalyzer {
         new_file
     }
 
+    /// Drops the cached analysis of a file that changed on disk. If `index` is
+    /// true, new and modified files are analyzed again so that they show up in
+    /// `scan_files`.
+    pub fn file_changed(&self, path: &Path, index: bool, request_time: Instant) {
+        let entry = self.cache.read().unwrap().get(path).cloned();
+        if let Some(entry) = entry {
+            if let Some(file) = entry.lock().unwrap().as_ref() {
+                file.key.invalidate();
+            }
+        }
+        if path.file_name().is_some_and(|name| name == "args.gn") {
+            *self.args_files.write().unwrap() = None;
+            *self.output_dirs.write().unwrap() = None;
+        }
+
+        if !path.exists() {
+            self.cache.write().unwrap().remove(path);
+        } else if index && is_good_for_scan(path) && is_in_source_dirs(path, &self.context.root) {
+            self.analyze_file(path, request_time);
+        }
+    }
+
     pub fn analyze_files(&self, path: &Path, request_time: Instant) -> OwnedEnvironment {
         let mut files: Vec<Arc<AnalyzedFile>> = Vec::new();
         self.collect_imports(path, request_time, &mut files, &mut HashSet::new());
@@ -256,27 +410,185 @@ impl WorkspaceAnalyzer {
         pos: usize,
         request_time: Instant,
     ) -> OwnedEnvironment {
+        let (files, num_args_files) = self.collect_files_at(file, request_time);
+        OwnedEnvironment::new(files, |files| {
+            build_environment_at(files, num_args_files, pos)
+        })
+    }
+
+    /// Returns a hash of the versions of the file and the files making up its
+    /// environment. It changes whenever the analysis of the file may change.
+    pub fn environment_key(&self, file: &Arc<AnalyzedFile>, request_time: Instant) -> u64 {
+        let (files, _) = self.collect_files_at(file, request_time);
+        let mut hasher = DefaultHasher::new();
+        for file in &files {
+            file.document.path.hash(&mut hasher);
+            file.document.version.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Returns the members of the scope variable `name` at `pos` as variables
+    /// of an environment. Members of `invoker` are collected from the
+    /// invocations of the enclosing template in the files analyzed so far;
+    /// this does not wait for indexing to finish.
+    pub fn analyze_scope_at(
+        &self,
+        file: &Arc<AnalyzedFile>,
+        pos: usize,
+        name: &str,
+        request_time: Instant,
+    ) -> OwnedEnvironment {
+        let (mut files, num_args_files) = self.collect_files_at(file, request_time);
+        let num_environment_files = files.len();
+
+        let template_name = if name == INVOKER {
+            file.analyzed_root
+                .get()
+                .enclosing_template_at(pos)
+                .and_then(|template| template.name.as_simple_string())
+                .map(|name| name.to_string())
+        } else {
+            None
+        };
+
+        if let Some(template_name) = &template_name {
+            files.push(file.clone());
+            files.extend(self.cached_files().into_iter().filter(|other| {
+                other.document.path != file.document.path
+                    && other.target_functions.contains(template_name)
+            }));
+        } else {
+            let read_paths: Vec<PathBuf> = {
+                let environment = build_environment_at(&files, num_args_files, pos);
+                environment
+                    .variables
+                    .get(name)
+                    .into_iter()
+                    .flat_map(|variable| &variable.assignments)
+                    .filter_map(|assignment| {
+                        let Either::Left(statement) = assignment.assignment_or_call else {
+                            return None;
+                        };
+                        read_file_scope_path(&statement.rvalue, assignment.document, &self.context)
+                    })
+                    .collect()
+            };
+            for path in read_paths {
+                if path.exists() {
+                    files.push(self.analyze_file(&path, request_time));
+                }
+            }
+        }
+
+        OwnedEnvironment::new(files, |files| {
+            let mut members = Environment::new();
+            members.variables = match &template_name {
+                Some(template_name) => {
+                    collect_invoker_members(template_name, &files[num_environment_files..])
+                }
+                None => {
+                    let environment =
+                        build_environment_at(&files[..num_environment_files], num_args_files, pos);
+                    match environment.variables.get(name) {
+                        Some(variable) => collect_scope_members(variable, files, &self.context),
+                        None => Default::default(),
+                    }
+                }
+            };
+            members
+        })
+    }
+
+    /// Returns the files making up the environment at a position of a file,
+    /// i.e. the file itself, its imports and args files, along with the number
+    /// of the args files placed at the end.
+    fn collect_files_at(
+        &self,
+        file: &Arc<AnalyzedFile>,
+        request_time: Instant,
+    ) -> (Vec<Arc<AnalyzedFile>>, usize) {
         let mut files: Vec<Arc<AnalyzedFile>> = vec![file.clone()];
-        let mut visited = HashSet::from([file.document.path.clone()]);
+        files.extend(self.collect_imports_of(file, request_time));
+        let args_files = self.collect_args_files(request_time);
+        let num_args_files = args_files.len();
+        files.extend(args_files);
+        (files, num_args_files)
+    }
 
-        // Collect BUILDCONFIG.gn.
-        self.collect_imports(
-            &self.context.build_config.clone(),
-            request_time,
-            &mut files,
-            &mut visited,
-        );
+    /// Returns `args.gn` of the active build directory and the files it
+    /// imports. They are cached until any of them changes.
+    fn collect_args_files(&self, request_time: Instant) -> Vec<Arc<AnalyzedFile>> {
+        let Some(build_dir) = self.active_build_dir() else {
+            return Vec::new();
+        };
 
-        // Collect imported files.
-        for child_path in &file.exports.get().children {
-            self.collect_imports(child_path, request_time, &mut files, &mut visited);
+        if let Some(cached) = self.args_files.read().unwrap().as_ref() {
+            let storage = self.storage.lock().unwrap();
+            if cached.build_dir == build_dir
+                && cached.key.verify(request_time, &storage)
+                && cached
+                    .files
+                    .iter()
+                    .all(|file| file.key.verify(request_time, &storage))
+            {
+                return cached.files.clone();
+            }
         }
 
+        let args_path = build_dir.join("args.gn");
+        let version = self.storage.lock().unwrap().read_version(&args_path);
+        let key = CacheKey::new(args_path.clone(), version, request_time);
+        let mut files = Vec::new();
+        if args_path.exists() {
+            self.collect_imports(&args_path, request_time, &mut files, &mut HashSet::new());
+        }
+        *self.args_files.write().unwrap() = Some(ArgsFiles {
+            build_dir,
+            key,
+            files: files.clone(),
+        });
+        files
+    }
+
+    /// Returns values of build arguments set in `args.gn` of the active build
+    /// directory, as far as they can be evaluated statically.
+    pub fn active_args(&self, request_time: Instant) -> KnownValues {
+        let mut values = KnownValues::new();
+        // Apply imported files first so that importing files can override them.
+        for args_file in self.collect_args_files(request_time).iter().rev() {
+            // Assignments in conditions are not evaluated.
+            for statement in &args_file.parsed_root.get().statements {
+                let Statement::Assignment(assignment) = statement else {
+                    continue;
+                };
+                let LValue::Identifier(identifier) = &assignment.lvalue else {
+                    continue;
+                };
+                if assignment.op != AssignOp::Assign {
+                    continue;
+                }
+                match evaluate(&assignment.rvalue, &values) {
+                    Some(value) => values.insert(identifier.name.to_string(), value),
+                    None => values.remove(identifier.name),
+                };
+            }
+        }
+        values
+    }
+
+    /// Returns the environment a file starts with, i.e. symbols exported from
+    /// BUILDCONFIG.gn and the imported files, excluding the file's own symbols.
+    pub fn analyze_imports(
+        &self,
+        file: &Arc<AnalyzedFile>,
+        request_time: Instant,
+    ) -> OwnedEnvironment {
+        let files = self.collect_imports_of(file, request_time);
+
         OwnedEnvironment::new(files, |files| {
             let mut environment = Environment::new();
-            let (current_file, imported_files) = files.split_first().unwrap();
-
-            for file in imported_files.iter().rev() {
+            for file in files.iter().rev() {
                 environment
                     .variables
                     .extend(file.exports.get().variables.clone());
@@ -284,18 +596,34 @@ impl WorkspaceAnalyzer {
                     .templates
                     .extend(file.exports.get().templates.clone());
             }
-
-            environment
-                .variables
-                .extend(current_file.local_variables_at(pos));
-            environment
-                .templates
-                .extend(current_file.local_templates_at(pos));
-
             environment
         })
     }
 
+    fn collect_imports_of(
+        &self,
+        file: &Arc<AnalyzedFile>,
+        request_time: Instant,
+    ) -> Vec<Arc<AnalyzedFile>> {
+        let mut files: Vec<Arc<AnalyzedFile>> = Vec::new();
+        let mut visited = HashSet::from([file.document.path.clone()]);
+
+        // Collect BUILDCONFIG.gn.
+        self.collect_imports(
+            &self.context.build_config.clone(),
+            request_time,
+            &mut files,
+            &mut visited,
+        );
+
+        // Collect imported files.
+        for child_path in &file.exports.get().children {
+            self.collect_imports(child_path, request_time, &mut files, &mut visited);
+        }
+
+        files
+    }
+
     fn collect_imports(
         &self,
         path: &Path,
@@ -420,6 +748,8 @@ impl WorkspaceAnalyzer {
                             call,
                             expr_scopes,
                             includes: &call.args[1],
+                            excludes: call.args.get(2),
+                            wildcard_parameters: Vec::new(),
                         },
                     ));
                 }
@@ -438,8 +768,9 @@ impl WorkspaceAnalyzer {
                 }
                 None
             }
-            (TEMPLATE, Some(body_block)) => {
+            (TEMPLATE, Some(mut body_block)) => {
                 if let Some(name) = call.only_arg() {
+                    body_block.set_wildcard_parameters(&collect_template_parameters(call));
                     let expr_scopes = call
                         .args
                         .iter()
@@ -584,6 +915,7 @@ impl WorkspaceAnalyzer {
                             .push(VariableAssignment {
                                 document,
                                 assignment_or_call: Either::Left(assignment),
+                                name: identifier.name,
                                 primary_variable: identifier.span,
                                 comments: assignment.comments.clone(),
                             });
@@ -648,6 +980,7 @@ impl WorkspaceAnalyzer {
                                             .push(VariableAssignment {
                                                 document,
                                                 assignment_or_call: Either::Right(call),
+                                                name,
                                                 primary_variable: string.span,
                                                 comments: Comments::default(),
                                             });
@@ -680,3 +1013,51 @@ impl WorkspaceAnalyzer {
         exports
     }
 }
+
+fn build_environment_at(
+    files: &[Arc<AnalyzedFile>],
+    num_args_files: usize,
+    pos: usize,
+) -> Environment<'_> {
+    let mut environment = Environment::new();
+    let (files, args_files) = files.split_at(files.len() - num_args_files);
+    let (current_file, imported_files) = files.split_first().unwrap();
+
+    for file in imported_files.iter().rev() {
+        environment
+            .variables
+            .extend(file.exports.get().variables.clone());
+        environment
+            .templates
+            .extend(file.exports.get().templates.clone());
+    }
+
+    // Drop variables forwarded by "*" but excluded by a list defined in the
+    // imports, e.g. `forward_variables_from(invoker, "*", TESTONLY_AND_VISIBILITY)`.
+    let mut local_variables = current_file.local_variables_at(pos);
+    local_variables.retain(|_, variable| {
+        variable
+            .assignments
+            .retain(|assignment| !assignment.is_excluded_by(&environment.variables));
+        !variable.assignments.is_empty()
+    });
+    environment.variables.extend(local_variables);
+    environment
+        .templates
+        .extend(current_file.local_templates_at(pos));
+
+    // Apply args.gn, where later files are imported by earlier ones.
+    for args_file in args_files.iter().rev() {
+        for (name, args_variable) in &args_file.exports.get().variables {
+            if let Some(variable) = environment
+                .variables
+                .get_mut(name)
+                .filter(|variable| variable.is_args)
+            {
+                variable.args_override = args_variable.assignments.last().cloned();
+            }
+        }
+    }
+
+    environment
+}
@@ -0,0 +1,206 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+
+use pest::Span;
+
+use crate::{
+    common::builtins::{ASSERT, DEFINED, FORWARD_VARIABLES_FROM, INVOKER, TEMPLATE},
+    parser::{
+        string_content_span, BinaryOp, Call, Expr, Node, PrimaryExpr, ScopeAccess, Statement,
+    },
+};
+
+/// Variable a template reads from its invoker.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TemplateParameter<'p> {
+    pub name: &'p str,
+    /// Span of the first use in the template body.
+    pub span: Span<'p>,
+    /// Whether the template asserts that the variable is defined. Invocations
+    /// not assigning it are reported.
+    pub required: bool,
+    /// Whether the template reads the variable without checking that it is
+    /// defined or forwarding it, which fails unless it is assigned.
+    pub read_unconditionally: bool,
+    /// `assert(defined(invoker.<name>), ...)` at the top level of the template
+    /// body, declaring the parameter as required.
+    pub assertion: Option<&'p Call<'p>>,
+}
+
+impl<'p> TemplateParameter<'p> {
+    /// Returns the message of the assertion declaring the parameter.
+    pub fn assertion_message(&self) -> Option<&'p str> {
+        self.assertion?
+            .args
+            .get(1)?
+            .as_primary_string()
+            .map(|string| string.raw_value)
+    }
+}
+
+#[derive(Default)]
+struct ParameterUses<'p> {
+    parameters: Vec<TemplateParameter<'p>>,
+    optional: HashSet<&'p str>,
+    defined_checks: HashSet<Span<'p>>,
+}
+
+impl<'p> ParameterUses<'p> {
+    fn add(&mut self, name: &'p str, span: Span<'p>, optional: bool) {
+        if optional {
+            self.optional.insert(name);
+        }
+        if !self
+            .parameters
+            .iter()
+            .any(|parameter| parameter.name == name)
+        {
+            self.parameters.push(TemplateParameter {
+                name,
+                span,
+                required: false,
+                read_unconditionally: false,
+                assertion: None,
+            });
+        }
+    }
+
+    fn visit_call(&mut self, call: &'p Call<'p>) {
+        match call.function.name {
+            DEFINED => {
+                if let Some(scope_access) = call.only_arg().and_then(as_invoker_access) {
+                    self.defined_checks.insert(scope_access.span);
+                    self.add(scope_access.member.name, scope_access.member.span, true);
+                }
+            }
+            FORWARD_VARIABLES_FROM => {
+                if !call.args.first().is_some_and(is_invoker) {
+                    return;
+                }
+                let Some(list) = call.args.get(1).and_then(|arg| arg.as_primary_list()) else {
+                    return;
+                };
+                for value in &list.values {
+                    if let Some(string) = value.as_primary_string() {
+                        if let Some(name) = value.as_simple_string() {
+                            self.add(name, string_content_span(string.span), true);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit(&mut self, node: &'p dyn Node<'p>) {
+        if let Some(call) = node.as_call() {
+            // Nested templates have their own invokers.
+            if call.function.name == TEMPLATE {
+                return;
+            }
+            self.visit_call(call);
+        }
+        if let Some(scope_access) = node.as_scope_access() {
+            if scope_access.scope.name == INVOKER
+                && !self.defined_checks.contains(&scope_access.span)
+            {
+                self.add(scope_access.member.name, scope_access.member.span, false);
+            }
+        }
+        for child in node.children() {
+            self.visit(child);
+        }
+    }
+}
+
+fn as_invoker_access<'a, 'p>(expr: &'a Expr<'p>) -> Option<&'a ScopeAccess<'p>> {
+    match expr.as_primary()? {
+        PrimaryExpr::ScopeAccess(scope_access) if scope_access.scope.name == INVOKER => {
+            Some(scope_access)
+        }
+        _ => None,
+    }
+}
+
+/// Returns whether the expression is the `invoker` identifier.
+pub(crate) fn is_invoker(expr: &Expr) -> bool {
+    expr.as_primary_identifier()
+        .is_some_and(|identifier| identifier.name == INVOKER)
+}
+
+/// Collects `invoker.<name>` checked by `defined()` in a conjunction.
+fn collect_asserted_definitions<'p>(expr: &Expr<'p>, names: &mut Vec<&'p str>) {
+    match expr {
+        Expr::Binary(binary_expr) if binary_expr.op == BinaryOp::And => {
+            collect_asserted_definitions(&binary_expr.lhs, names);
+            collect_asserted_definitions(&binary_expr.rhs, names);
+        }
+        Expr::Primary(primary_expr) => match primary_expr.as_ref() {
+            PrimaryExpr::ParenExpr(paren_expr) => {
+                collect_asserted_definitions(&paren_expr.expr, names);
+            }
+            PrimaryExpr::Call(call) if call.function.name == DEFINED => {
+                if let Some(scope_access) = call.only_arg().and_then(as_invoker_access) {
+                    names.push(scope_access.member.name);
+                }
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+/// Infers the parameters of a template from the uses of `invoker` in its body:
+/// reads of `invoker.<name>`, `defined(invoker.<name>)` checks, and lists of
+/// `forward_variables_from(invoker, [...])`. A parameter is required if the
+/// body asserts that it is defined at the top level. It is read
+/// unconditionally if it is never checked nor forwarded in the body.
+pub fn collect_template_parameters<'p>(call: &'p Call<'p>) -> Vec<TemplateParameter<'p>> {
+    let Some(block) = &call.block else {
+        return Vec::new();
+    };
+    let mut assertions: HashMap<&str, &Call> = HashMap::new();
+    for statement in &block.statements {
+        let Statement::Call(assert_call) = statement else {
+            continue;
+        };
+        if assert_call.function.name != ASSERT {
+            continue;
+        }
+        let Some(condition) = assert_call.args.first() else {
+            continue;
+        };
+        let mut names = Vec::new();
+        collect_asserted_definitions(condition, &mut names);
+        for name in names {
+            assertions.entry(name).or_insert(assert_call);
+        }
+    }
+
+    let mut uses = ParameterUses::default();
+    uses.visit(block);
+    let ParameterUses {
+        mut parameters,
+        optional,
+        ..
+    } = uses;
+    for parameter in &mut parameters {
+        parameter.assertion = assertions.get(parameter.name).copied();
+        parameter.required = parameter.assertion.is_some();
+        parameter.read_unconditionally = !optional.contains(parameter.name);
+    }
+    parameters
+}
@@ -0,0 +1,140 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{path::PathBuf, sync::Arc};
+
+use either::Either;
+
+use crate::{
+    analyzer::{
+        data::VariableMap, AnalyzedFile, AnalyzedStatement, Variable, VariableAssignment,
+        WorkspaceContext,
+    },
+    common::{builtins::READ_FILE, storage::Document},
+    parser::{Assignment, Expr, LValue, PrimaryExpr},
+};
+
+fn merge_members<'p>(members: &mut VariableMap<'p>, variables: VariableMap<'p>) {
+    for (name, variable) in variables {
+        members
+            .entry(name)
+            .or_insert_with(|| Variable::new(name, false))
+            .assignments
+            .extend(variable.assignments);
+    }
+}
+
+fn find_file<'p>(files: &'p [Arc<AnalyzedFile>], document: &Document) -> Option<&'p AnalyzedFile> {
+    files
+        .iter()
+        .find(|file| std::ptr::eq(file.document.as_ref(), document))
+        .map(|file| file.as_ref())
+}
+
+/// Returns the path of the file read by `read_file(path, "scope")`.
+pub fn read_file_scope_path(
+    rvalue: &Expr,
+    document: &Document,
+    context: &WorkspaceContext,
+) -> Option<PathBuf> {
+    let PrimaryExpr::Call(call) = rvalue.as_primary()? else {
+        return None;
+    };
+    if call.function.name != READ_FILE || call.args.len() != 2 {
+        return None;
+    }
+    if call.args[1].as_simple_string()? != "scope" {
+        return None;
+    }
+    let name = call.args[0].as_simple_string()?;
+    Some(context.resolve_path(name, document.path.parent()?))
+}
+
+/// Collects members of a scope variable from its assignments: block literals,
+/// scopes read with `read_file`, and assignments to individual members.
+/// `files` must contain the files of the assignments and the files read.
+pub fn collect_scope_members<'p>(
+    variable: &Variable<'p>,
+    files: &'p [Arc<AnalyzedFile>],
+    context: &WorkspaceContext,
+) -> VariableMap<'p> {
+    let mut members = VariableMap::new();
+    for assignment in &variable.assignments {
+        let Either::Left(statement) = assignment.assignment_or_call else {
+            continue;
+        };
+        match &statement.lvalue {
+            LValue::ScopeAccess(scope_access) => {
+                members
+                    .entry(scope_access.member.name)
+                    .or_insert_with(|| Variable::new(scope_access.member.name, false))
+                    .assignments
+                    .push(VariableAssignment {
+                        document: assignment.document,
+                        assignment_or_call: Either::Left(statement),
+                        name: scope_access.member.name,
+                        primary_variable: scope_access.member.span,
+                        comments: statement.comments.clone(),
+                    });
+            }
+            LValue::Identifier(_) => {
+                merge_members(
+                    &mut members,
+                    assigned_scope_members(statement, assignment.document, files, context),
+                );
+            }
+            LValue::ArrayAccess(_) => {}
+        }
+    }
+    members
+}
+
+fn assigned_scope_members<'p>(
+    statement: &Assignment,
+    document: &Document,
+    files: &'p [Arc<AnalyzedFile>],
+    context: &WorkspaceContext,
+) -> VariableMap<'p> {
+    if let Some(PrimaryExpr::Block(block)) = statement.rvalue.as_primary() {
+        if let Some(scope) = find_file(files, document)
+            .and_then(|file| file.analyzed_root.get().find_scope(block.span))
+        {
+            return scope.variables();
+        }
+    } else if let Some(path) = read_file_scope_path(&statement.rvalue, document, context) {
+        if let Some(file) = files.iter().find(|file| file.document.path == path) {
+            return file.analyzed_root.get().variables();
+        }
+    }
+    VariableMap::new()
+}
+
+/// Collects members of `invoker` in a template, i.e. variables set in the
+/// blocks of the template invocations found in `files`.
+pub fn collect_invoker_members<'p>(
+    template_name: &str,
+    files: &'p [Arc<AnalyzedFile>],
+) -> VariableMap<'p> {
+    let mut members = VariableMap::new();
+    for file in files {
+        for statement in file.analyzed_root.get().all_statements() {
+            if let AnalyzedStatement::Target(target) = statement {
+                if target.call.function.name == template_name {
+                    merge_members(&mut members, target.body_block.variables());
+                }
+            }
+        }
+    }
+    members
+}
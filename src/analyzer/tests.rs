@@ -15,14 +15,15 @@
 #![cfg(test)]
 
 use std::{
+    path::Path,
     sync::{Arc, Mutex},
     time::Instant,
 };
 
 use crate::{
-    analyzer::{Analyzer, IndexingLevel},
+    analyzer::{evaluate, Analyzer, IndexingLevel, IndexingProgress, KnownValues, Value},
     common::{storage::DocumentStorage, testutils::testdata, workspace::WorkspaceFinder},
-    parser::Statement,
+    parser::{parse, Statement},
 };
 
 #[test]
@@ -32,6 +33,7 @@ fn test_analyze_smoke() {
         &storage,
         WorkspaceFinder::new(None),
         IndexingLevel::Disabled,
+        None,
     );
 
     let file = analyzer
@@ -61,6 +63,7 @@ fn test_analyze_cycles() {
         &storage,
         WorkspaceFinder::new(None),
         IndexingLevel::Disabled,
+        None,
     );
 
     assert!(analyzer
@@ -70,3 +73,266 @@ fn test_analyze_cycles() {
         .analyze_file(&testdata("workspaces/cycles/bad1.gni"), request_time)
         .is_ok());
 }
+
+#[tokio::test]
+async fn test_file_changed() {
+    // Avoid the default prefix, as indexing skips dot directories.
+    let dir = tempfile::Builder::new().prefix("gn").tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join(".gn"), "buildconfig = \"//BUILDCONFIG.gn\"\n").unwrap();
+    std::fs::write(root.join("BUILDCONFIG.gn"), "").unwrap();
+    std::fs::write(root.join("BUILD.gn"), "a = 1\n").unwrap();
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(
+        &storage,
+        WorkspaceFinder::new(None),
+        IndexingLevel::Enabled { parallel: false },
+        None,
+    );
+    let workspace = analyzer.workspace_for(&root.join("BUILD.gn")).unwrap();
+
+    let scanned_paths = || async {
+        let mut paths: Vec<_> = workspace
+            .scan_files()
+            .await
+            .into_iter()
+            .map(|file| file.document.path.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        paths.sort();
+        paths
+    };
+    assert_eq!(
+        scanned_paths().await,
+        [Path::new("BUILD.gn"), Path::new("BUILDCONFIG.gn")]
+    );
+
+    // Modified files are analyzed again without waiting for verification.
+    let file = analyzer
+        .analyze_file(&root.join("BUILD.gn"), Instant::now())
+        .unwrap();
+    assert!(file.exports.get().variables.contains_key("a"));
+    std::fs::write(root.join("BUILD.gn"), "b = 1\n").unwrap();
+    analyzer.file_changed(&root.join("BUILD.gn"), Instant::now());
+    let file = analyzer
+        .analyze_file(&root.join("BUILD.gn"), Instant::now())
+        .unwrap();
+    assert!(file.exports.get().variables.contains_key("b"));
+
+    // New files are indexed, except for ones in output directories.
+    std::fs::create_dir_all(root.join("foo")).unwrap();
+    std::fs::write(root.join("foo/BUILD.gn"), "").unwrap();
+    analyzer.file_changed(&root.join("foo/BUILD.gn"), Instant::now());
+    std::fs::create_dir_all(root.join("out/gen")).unwrap();
+    std::fs::write(root.join("out/args.gn"), "").unwrap();
+    std::fs::write(root.join("out/gen/foo.gni"), "").unwrap();
+    analyzer.file_changed(&root.join("out/gen/foo.gni"), Instant::now());
+    assert_eq!(
+        scanned_paths().await,
+        [
+            Path::new("BUILD.gn"),
+            Path::new("BUILDCONFIG.gn"),
+            Path::new("foo/BUILD.gn")
+        ]
+    );
+
+    // Deleted files are removed from the index.
+    std::fs::remove_file(root.join("foo/BUILD.gn")).unwrap();
+    analyzer.file_changed(&root.join("foo/BUILD.gn"), Instant::now());
+    assert_eq!(
+        scanned_paths().await,
+        [Path::new("BUILD.gn"), Path::new("BUILDCONFIG.gn")]
+    );
+}
+
+#[test]
+fn test_args_files_cache() {
+    let dir = tempfile::Builder::new().prefix("gn").tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join(".gn"), "buildconfig = \"//BUILDCONFIG.gn\"\n").unwrap();
+    std::fs::write(root.join("BUILDCONFIG.gn"), "").unwrap();
+    std::fs::create_dir_all(root.join("out/Debug")).unwrap();
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(
+        &storage,
+        WorkspaceFinder::new(None),
+        IndexingLevel::Disabled,
+        None,
+    );
+    let workspace = analyzer
+        .workspace_for(&root.join("BUILDCONFIG.gn"))
+        .unwrap();
+    workspace.set_active_build_dir(Some(root.join("out/Debug")));
+    assert!(workspace.collect_args_files(Instant::now()).is_empty());
+
+    // A new args.gn is picked up.
+    let args_path = root.join("out/Debug/args.gn");
+    std::fs::write(&args_path, "is_debug = true\n").unwrap();
+    analyzer.file_changed(&args_path, Instant::now());
+    let files = workspace.collect_args_files(Instant::now());
+    assert_eq!(files.len(), 1);
+
+    // Files are reused until they change.
+    let request_time = Instant::now();
+    assert!(Arc::ptr_eq(
+        &files[0],
+        &workspace.collect_args_files(request_time)[0]
+    ));
+    std::fs::write(&args_path, "is_debug = false\n").unwrap();
+    analyzer.file_changed(&args_path, Instant::now());
+    assert_eq!(
+        workspace.active_args(Instant::now()),
+        KnownValues::from([("is_debug".to_string(), Value::Bool(false))])
+    );
+}
+
+#[tokio::test]
+async fn test_indexing_progress() {
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(
+        &storage,
+        WorkspaceFinder::new(None),
+        IndexingLevel::Enabled { parallel: true },
+        Some(sender),
+    );
+    let workspace = analyzer
+        .workspace_for(&testdata("workspaces/smoke/BUILD.gn"))
+        .unwrap();
+    let files = workspace.scan_files().await;
+
+    let root = workspace.context().root.clone();
+    assert_eq!(
+        receiver.recv().await,
+        Some(IndexingProgress::Begin { root: root.clone() })
+    );
+    let mut last = None;
+    while let Some(event) = receiver.recv().await {
+        let end = matches!(event, IndexingProgress::End { .. });
+        last = Some(event);
+        if end {
+            break;
+        }
+    }
+    let Some(IndexingProgress::End {
+        root: end_root,
+        processed,
+        ..
+    }) = last
+    else {
+        panic!("indexing did not end: {last:?}");
+    };
+    assert_eq!(end_root, root);
+    assert_eq!(processed, files.len());
+}
+
+fn evaluate_str(input: &str, values: &KnownValues) -> Option<Value> {
+    let root = parse(input);
+    let Some(Statement::Assignment(assignment)) = root.statements.first() else {
+        panic!("not an assignment: {input}");
+    };
+    evaluate(&assignment.rvalue, values)
+}
+
+#[test]
+fn test_evaluate() {
+    let values = KnownValues::from([
+        ("is_linux".to_string(), Value::Bool(true)),
+        ("is_win".to_string(), Value::Bool(false)),
+        ("target_cpu".to_string(), Value::String("x64".to_string())),
+        ("level".to_string(), Value::Integer(2)),
+    ]);
+
+    assert_eq!(
+        evaluate_str("a = is_linux && !is_win", &values),
+        Some(Value::Bool(true))
+    );
+    assert_eq!(
+        evaluate_str("a = target_cpu == \"arm64\" || level >= 2", &values),
+        Some(Value::Bool(true))
+    );
+    assert_eq!(
+        evaluate_str("a = (level + 1) < 3", &values),
+        Some(Value::Bool(false))
+    );
+
+    // Unknown variables are tolerated if they don't affect the result.
+    assert_eq!(
+        evaluate_str("a = is_unknown && is_win", &values),
+        Some(Value::Bool(false))
+    );
+    assert_eq!(evaluate_str("a = is_unknown && is_linux", &values), None);
+    assert_eq!(
+        evaluate_str("a = \"$target_cpu\" == \"x64\"", &values),
+        None
+    );
+}
+
+#[test]
+fn test_client_roots() {
+    let outer = testdata("workspaces/workspace_folders");
+    let inner = outer.join("nested");
+    let tools = outer.join("tools");
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(
+        &storage,
+        WorkspaceFinder::new(None),
+        IndexingLevel::Disabled,
+        None,
+    );
+    let roots = || analyzer.workspaces().into_keys().collect::<Vec<_>>();
+
+    // Workspaces of added folders are analyzed right away.
+    analyzer.add_client_root(&tools);
+    analyzer.add_client_root(&inner);
+    assert_eq!(roots(), [outer.clone(), inner.clone()]);
+
+    // Workspaces are dropped once no folder uses them.
+    analyzer.remove_client_root(&inner);
+    assert_eq!(roots(), [outer]);
+    analyzer.remove_client_root(&tools);
+    assert!(roots().is_empty());
+}
+
+#[test]
+fn test_output_dirs_cache() {
+    let dir = tempfile::Builder::new().prefix("gn").tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join(".gn"), "buildconfig = \"//BUILDCONFIG.gn\"\n").unwrap();
+    std::fs::write(root.join("BUILDCONFIG.gn"), "").unwrap();
+    std::fs::create_dir_all(root.join("out/Debug")).unwrap();
+    std::fs::create_dir_all(root.join("out/Release")).unwrap();
+    std::fs::write(root.join("out/Debug/args.gn"), "").unwrap();
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    storage
+        .lock()
+        .unwrap()
+        .set_watched_roots(vec![root.to_path_buf()]);
+    let analyzer = Analyzer::new(
+        &storage,
+        WorkspaceFinder::new(None),
+        IndexingLevel::Disabled,
+        None,
+    );
+    let workspace = analyzer
+        .workspace_for(&root.join("BUILDCONFIG.gn"))
+        .unwrap();
+    assert_eq!(workspace.output_dirs(), [root.join("out/Debug")]);
+
+    // The tree is walked again only after an args.gn is reported.
+    let args_path = root.join("out/Release/args.gn");
+    std::fs::write(&args_path, "").unwrap();
+    assert_eq!(workspace.output_dirs(), [root.join("out/Debug")]);
+    analyzer.file_changed(&args_path, Instant::now());
+    assert_eq!(
+        workspace.output_dirs(),
+        [root.join("out/Debug"), root.join("out/Release")]
+    );
+
+    // Deleted directories are dropped.
+    std::fs::remove_dir_all(root.join("out/Debug")).unwrap();
+    assert_eq!(workspace.output_dirs(), [root.join("out/Release")]);
+}
@@ -33,6 +33,7 @@ pub async fn run_bench(workspace_root: &Path) {
         &storage,
         WorkspaceFinder::new(Some(workspace_root)),
         IndexingLevel::Disabled,
+        None,
     ));
 
     let start_time = Instant::now();
@@ -43,8 +44,12 @@ pub async fn run_bench(workspace_root: &Path) {
         let analyzer = analyzer.clone();
         tasks.push(tokio::spawn(async move {
             if let Ok(file) = analyzer.analyze_file(&path, start_time) {
-                let diagnostics =
-                    crate::diagnostics::compute_diagnostics(&file, &analyzer, start_time);
+                let diagnostics = crate::diagnostics::compute_diagnostics(
+                    &file,
+                    &analyzer,
+                    &Default::default(),
+                    start_time,
+                );
                 for d in diagnostics {
                     println!(
                         "{}:{}:{}: {}",
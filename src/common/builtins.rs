@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub const ASSERT: &str = "assert";
 pub const IMPORT: &str = "import";
 pub const TEMPLATE: &str = "template";
 pub const DECLARE_ARGS: &str = "declare_args";
@@ -20,6 +21,8 @@ pub const SET_DEFAULTS: &str = "set_defaults";
 pub const FORWARD_VARIABLES_FROM: &str = "forward_variables_from";
 pub const DEFINED: &str = "defined";
 pub const VISIBILITY: &str = "visibility";
+pub const INVOKER: &str = "invoker";
+pub const READ_FILE: &str = "read_file";
 
 pub struct BuiltinSymbol {
     pub name: &'static str,
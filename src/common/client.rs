@@ -15,7 +15,14 @@
 use std::fmt::Display;
 
 use itertools::Itertools;
-use tower_lsp::lsp_types::{ConfigurationItem, Diagnostic, MessageType, Url};
+use tower_lsp::{
+    jsonrpc::Result,
+    lsp_types::{
+        notification::Progress, request::WorkDoneProgressCreate, ConfigurationItem, Diagnostic,
+        MessageType, ProgressParams, ProgressParamsValue, ProgressToken, Registration,
+        ShowDocumentParams, Url, WorkDoneProgress, WorkDoneProgressCreateParams,
+    },
+};
 
 use crate::common::config::Configurations;
 
@@ -42,6 +49,19 @@ impl TestableClient {
         }
     }
 
+    pub async fn show_message<M: Display>(&self, typ: MessageType, message: M) {
+        if let Some(client) = &self.client {
+            client.show_message(typ, message).await;
+        }
+    }
+
+    pub async fn show_document(&self, params: ShowDocumentParams) -> Result<bool> {
+        match &self.client {
+            Some(client) => client.show_document(params).await,
+            None => Ok(false),
+        }
+    }
+
     pub async fn configurations(&self) -> Configurations {
         let Some(client) = &self.client else {
             return Configurations::default();
@@ -64,6 +84,40 @@ impl TestableClient {
         serde_json::from_value(value).unwrap_or_default()
     }
 
+    pub async fn register_capability(&self, registrations: Vec<Registration>) -> Result<()> {
+        if let Some(client) = &self.client {
+            client.register_capability(registrations).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn create_work_done_progress(&self, token: ProgressToken) -> Result<()> {
+        if let Some(client) = &self.client {
+            client
+                .send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams { token })
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn work_done_progress(&self, token: ProgressToken, value: WorkDoneProgress) {
+        if let Some(client) = &self.client {
+            client
+                .send_notification::<Progress>(ProgressParams {
+                    token,
+                    value: ProgressParamsValue::WorkDone(value),
+                })
+                .await;
+        }
+    }
+
+    pub async fn workspace_diagnostic_refresh(&self) -> Result<()> {
+        if let Some(client) = &self.client {
+            client.workspace_diagnostic_refresh().await?;
+        }
+        Ok(())
+    }
+
     pub async fn publish_diagnostics(
         &self,
         uri: Url,
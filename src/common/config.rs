@@ -14,7 +14,7 @@
 
 use std::path::PathBuf;
 
-use crate::analyzer::IndexingLevel;
+use crate::analyzer::{IndexingLevel, KnownValues};
 
 fn default_true() -> bool {
     true
@@ -24,6 +24,9 @@ fn default_true() -> bool {
 #[serde(rename_all = "camelCase")]
 pub struct Configurations {
     pub binary_path: Option<PathBuf>,
+    pub active_build_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub assumed_args: KnownValues,
     #[serde(default = "default_true")]
     pub background_indexing: bool,
     #[serde(default = "default_true")]
@@ -34,6 +37,8 @@ pub struct Configurations {
     pub parallel_indexing: bool,
     #[serde(default = "default_true")]
     pub workspace_completion: bool,
+    #[serde(default)]
+    pub workspace_diagnostics: WorkspaceDiagnosticsScope,
     pub experimental: ExperimentalConfigurations,
 }
 
@@ -41,11 +46,14 @@ impl Default for Configurations {
     fn default() -> Self {
         Self {
             binary_path: None,
+            active_build_dir: None,
+            assumed_args: Default::default(),
             background_indexing: true,
             error_reporting: true,
             target_lens: true,
             parallel_indexing: true,
             workspace_completion: true,
+            workspace_diagnostics: Default::default(),
             experimental: Default::default(),
         }
     }
@@ -62,6 +70,18 @@ impl Configurations {
     }
 }
 
+/// Files to report diagnostics for, in addition to open documents.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceDiagnosticsScope {
+    #[default]
+    Off,
+    /// Indexed files under the folders opened in the client.
+    OpenFolders,
+    /// All indexed files of the workspaces.
+    Workspace,
+}
+
 #[derive(Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExperimentalConfigurations {}
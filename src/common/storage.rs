@@ -19,7 +19,9 @@ use std::{
     time::SystemTime,
 };
 
-use crate::common::utils::OwnedLineIndex;
+use tower_lsp::lsp_types::{Position, TextDocumentContentChangeEvent};
+
+use crate::common::utils::{LineIndex, OwnedLineIndex};
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum DocumentVersion {
@@ -75,9 +77,44 @@ impl PartialEq for Document {
 
 impl Eq for Document {}
 
+/// Converts a position to an offset. As the protocol requires, a character
+/// beyond the end of a line is clamped to the end of the line, excluding the
+/// line terminator, and a line beyond the end of the document to the end of
+/// the document.
+fn clamped_offset(line_index: &LineIndex, data: &str, position: Position) -> usize {
+    let Some(line_start) = line_index.offset(Position::new(position.line, 0)) else {
+        return data.len();
+    };
+    let line_end = data[line_start..]
+        .find('\n')
+        .map_or(data.len(), |index| line_start + index);
+    let content_end = if data[..line_end].ends_with('\r') {
+        line_end - 1
+    } else {
+        line_end
+    };
+    line_index
+        .offset(position)
+        .map_or(content_end, |offset| offset.min(content_end))
+}
+
+fn apply_change(data: &mut String, change: &TextDocumentContentChangeEvent) {
+    let Some(range) = change.range else {
+        *data = change.text.clone();
+        return;
+    };
+    let line_index = LineIndex::new(data);
+    let start = clamped_offset(&line_index, data, range.start);
+    let end = clamped_offset(&line_index, data, range.end).max(start);
+    data.replace_range(start..end, &change.text);
+}
+
 #[derive(Default)]
 pub struct DocumentStorage {
     memory_docs: BTreeMap<PathBuf, Arc<Document>>,
+    /// Directories whose files the client notifies changes of, if watchers
+    /// are registered.
+    watched_roots: Option<Vec<PathBuf>>,
 }
 
 impl DocumentStorage {
@@ -118,6 +155,21 @@ impl DocumentStorage {
         );
     }
 
+    /// Applies changes sent by the client to the document in order. Changes
+    /// without a range replace the whole content.
+    pub fn apply_changes(
+        &mut self,
+        path: &Path,
+        changes: &[TextDocumentContentChangeEvent],
+        revision: i32,
+    ) {
+        let mut data = self.read(path).data.to_string();
+        for change in changes {
+            apply_change(&mut data, change);
+        }
+        self.load_to_memory(path, &data, revision);
+    }
+
     pub fn unload_from_memory(&mut self, path: &Path) {
         self.memory_docs.remove(path);
     }
@@ -125,4 +177,88 @@ impl DocumentStorage {
     pub fn memory_docs(&self) -> Vec<Arc<Document>> {
         self.memory_docs.values().cloned().collect()
     }
+
+    pub fn is_in_memory(&self, path: &Path) -> bool {
+        self.memory_docs.contains_key(path)
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watched_roots.is_some()
+    }
+
+    pub fn set_watched_roots(&mut self, roots: Vec<PathBuf>) {
+        self.watched_roots = Some(roots);
+    }
+
+    /// Returns whether changes of the file on disk are notified by the client.
+    pub fn is_watched(&self, path: &Path) -> bool {
+        self.watched_roots
+            .as_ref()
+            .is_some_and(|roots| roots.iter().any(|root| path.starts_with(root)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Range;
+
+    use super::*;
+
+    fn change(range: Option<(u32, u32, u32, u32)>, text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: range.map(|(start_line, start_character, end_line, end_character)| {
+                Range::new(
+                    Position::new(start_line, start_character),
+                    Position::new(end_line, end_character),
+                )
+            }),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn apply_changes() {
+        let path = Path::new("/nonexistent/BUILD.gn");
+        let mut storage = DocumentStorage::new();
+        storage.load_to_memory(path, "a = 1\nb = 2\n", 1);
+
+        storage.apply_changes(
+            path,
+            &[
+                // Replace "1" with "10".
+                change(Some((0, 4, 0, 5)), "10"),
+                // Delete the second line.
+                change(Some((1, 0, 2, 0)), ""),
+                // Append at a character beyond the end of the line.
+                change(Some((0, 100, 0, 100)), " + 1"),
+                // Insert at a line beyond the end of the document.
+                change(Some((5, 0, 5, 0)), "c = 3\n"),
+            ],
+            2,
+        );
+        let doc = storage.read(path);
+        assert_eq!(doc.data.as_str(), "a = 10 + 1\nc = 3\n");
+        assert_eq!(doc.version, DocumentVersion::InMemory { revision: 2 });
+
+        storage.apply_changes(path, &[change(None, "d = 4\n")], 3);
+        assert_eq!(storage.read(path).data.as_str(), "d = 4\n");
+
+        // A character just past the end of a line does not reach the newline.
+        storage.load_to_memory(path, "ab\ncd\n", 4);
+        storage.apply_changes(path, &[change(Some((0, 3, 0, 3)), "x")], 5);
+        assert_eq!(storage.read(path).data.as_str(), "abx\ncd\n");
+
+        // Positions on CRLF lines are clamped before "\r".
+        storage.load_to_memory(path, "ab\r\ncd\r\n", 6);
+        storage.apply_changes(
+            path,
+            &[
+                change(Some((0, 3, 0, 3)), "x"),
+                change(Some((1, 3, 1, 10)), "y"),
+            ],
+            7,
+        );
+        assert_eq!(storage.read(path).data.as_str(), "abx\r\ncdy\r\n");
+    }
 }
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::{
+    ffi::OsStr,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -22,7 +23,7 @@ use self_cell::self_cell;
 use pest::Span;
 use tokio::sync::SetOnce;
 use tower_lsp::lsp_types::{Position, Range};
-use walkdir::{DirEntry, WalkDir};
+use walkdir::WalkDir;
 
 pub fn is_exported(name: &str) -> bool {
     !name.starts_with("_")
@@ -36,17 +37,13 @@ pub fn format_path(path: &Path, workspace_root: &Path) -> String {
     }
 }
 
-fn filter_source_entry(entry: &DirEntry) -> bool {
+fn filter_source_entry(file_name: &OsStr, path: &Path, is_dir: bool) -> bool {
     // Drop dot files.
-    if entry
-        .file_name()
-        .to_str()
-        .is_some_and(|name| name.starts_with('.'))
-    {
+    if file_name.to_str().is_some_and(|name| name.starts_with('.')) {
         return false;
     }
     // Drop output directories.
-    if entry.file_type().is_dir() && entry.path().join("args.gn").exists() {
+    if is_dir && path.join("args.gn").exists() {
         return false;
     }
     true
@@ -55,11 +52,48 @@ fn filter_source_entry(entry: &DirEntry) -> bool {
 pub fn walk_source_dirs(root: &Path) -> impl Iterator<Item = PathBuf> {
     WalkDir::new(root)
         .into_iter()
-        .filter_entry(filter_source_entry)
+        .filter_entry(|entry| {
+            filter_source_entry(entry.file_name(), entry.path(), entry.file_type().is_dir())
+        })
         .filter_map(|entry| entry.ok())
         .map(|entry| entry.into_path())
 }
 
+/// Returns output directories, i.e. the ones skipped by `walk_source_dirs`
+/// for containing `args.gn`.
+pub fn find_output_dirs(root: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let walker = WalkDir::new(root).into_iter().filter_entry(|entry| {
+        let is_dir = entry.file_type().is_dir();
+        if filter_source_entry(entry.file_name(), entry.path(), is_dir) {
+            return true;
+        }
+        if is_dir && entry.path().join("args.gn").exists() {
+            dirs.push(entry.path().to_path_buf());
+        }
+        false
+    });
+    walker.for_each(drop);
+    dirs.sort();
+    dirs
+}
+
+/// Returns whether `walk_source_dirs(root)` would visit the path.
+pub fn is_in_source_dirs(path: &Path, root: &Path) -> bool {
+    let Ok(relative_path) = path.strip_prefix(root) else {
+        return false;
+    };
+    let mut current = root.to_path_buf();
+    relative_path.components().all(|component| {
+        current.push(component);
+        // Symlinks are not followed, as in walk_source_dirs.
+        let is_dir = current
+            .symlink_metadata()
+            .is_ok_and(|metadata| metadata.is_dir());
+        filter_source_entry(component.as_os_str(), &current, is_dir)
+    })
+}
+
 pub fn is_good_for_scan(path: &Path) -> bool {
     path.file_name()
         .and_then(|name| name.to_str())
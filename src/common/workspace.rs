@@ -12,31 +12,99 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
 
 pub fn find_nearest_workspace_root(file: &Path) -> Option<&Path> {
     file.ancestors().find(|&dir| dir.join(".gn").exists())
 }
 
-#[derive(Clone, Debug)]
+/// Finds workspace roots for files, preferring workspaces containing folders
+/// opened by the client over workspaces nested in them.
+#[derive(Debug, Default)]
 pub struct WorkspaceFinder {
-    main_workspace_root: Option<PathBuf>,
+    /// Maps client workspace folders to the workspace roots containing them.
+    main_workspace_roots: RwLock<BTreeMap<PathBuf, PathBuf>>,
 }
 
 impl WorkspaceFinder {
-    pub fn new(client_root: Option<&Path>) -> Self {
-        let main_workspace_root = client_root.and_then(find_nearest_workspace_root);
-        Self {
-            main_workspace_root: main_workspace_root.map(|path| path.to_path_buf()),
+    pub fn new<'a>(client_roots: impl IntoIterator<Item = &'a Path>) -> Self {
+        let finder = Self::default();
+        for client_root in client_roots {
+            finder.add_client_root(client_root);
         }
+        finder
+    }
+
+    pub fn add_client_root(&self, client_root: &Path) {
+        if let Some(workspace_root) = find_nearest_workspace_root(client_root) {
+            self.main_workspace_roots
+                .write()
+                .unwrap()
+                .insert(client_root.to_path_buf(), workspace_root.to_path_buf());
+        }
+    }
+
+    pub fn remove_client_root(&self, client_root: &Path) {
+        self.main_workspace_roots
+            .write()
+            .unwrap()
+            .remove(client_root);
+    }
+
+    pub fn client_roots(&self) -> Vec<PathBuf> {
+        self.main_workspace_roots
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect()
     }
 
     pub fn find_for<'p>(&self, path: &'p Path) -> Option<&'p Path> {
-        if let Some(main_workspace_root) = &self.main_workspace_root {
-            if let Some(dir) = path.ancestors().find(|dir| dir == main_workspace_root) {
+        {
+            let main_workspace_roots = self.main_workspace_roots.read().unwrap();
+            if let Some(dir) = path.ancestors().find(|dir| {
+                main_workspace_roots
+                    .values()
+                    .any(|workspace_root| dir == workspace_root)
+            }) {
                 return Some(dir);
             }
         }
         find_nearest_workspace_root(path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[test]
+    fn workspace_finder() {
+        let outer = testdata("workspaces/workspace_folders");
+        let inner = outer.join("nested");
+        let file = inner.join("BUILD.gn");
+
+        let finder = WorkspaceFinder::new(None);
+        assert_eq!(finder.find_for(&file), Some(inner.as_path()));
+
+        finder.add_client_root(&outer.join("tools"));
+        assert_eq!(finder.find_for(&file), Some(outer.as_path()));
+
+        // The innermost main workspace wins.
+        finder.add_client_root(&inner);
+        assert_eq!(finder.find_for(&file), Some(inner.as_path()));
+
+        finder.remove_client_root(&inner);
+        assert_eq!(finder.find_for(&file), Some(outer.as_path()));
+
+        finder.remove_client_root(&outer.join("tools"));
+        assert_eq!(finder.find_for(&file), Some(inner.as_path()));
+    }
+}
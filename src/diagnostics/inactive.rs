@@ -0,0 +1,126 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Instant;
+
+use either::Either;
+use pest::Span;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag};
+
+use crate::{
+    analyzer::{evaluate, AnalyzedFile, Analyzer, KnownValues, Value},
+    common::storage::{Document, DocumentVersion},
+    parser::{Condition, Node, Statement},
+};
+
+fn inactive_diagnostic(span: Span, document: &Document) -> Diagnostic {
+    Diagnostic {
+        range: document.line_index.range(span),
+        severity: Some(DiagnosticSeverity::HINT),
+        message: "Inactive in the selected build configuration".to_string(),
+        tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+        ..Default::default()
+    }
+}
+
+fn collect_in_condition(
+    condition: &Condition,
+    document: &Document,
+    values: &KnownValues,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    match evaluate(&condition.condition, values) {
+        Some(Value::Bool(true)) => {
+            collect_in_node(&condition.then_block, document, values, diagnostics);
+            match &condition.else_block {
+                Some(Either::Left(else_condition)) => {
+                    diagnostics.push(inactive_diagnostic(else_condition.span, document));
+                }
+                Some(Either::Right(else_block)) => {
+                    diagnostics.push(inactive_diagnostic(else_block.span, document));
+                }
+                None => {}
+            }
+        }
+        Some(Value::Bool(false)) => {
+            diagnostics.push(inactive_diagnostic(condition.then_block.span, document));
+            match &condition.else_block {
+                Some(Either::Left(else_condition)) => {
+                    collect_in_condition(else_condition, document, values, diagnostics);
+                }
+                Some(Either::Right(else_block)) => {
+                    collect_in_node(else_block.as_ref(), document, values, diagnostics);
+                }
+                None => {}
+            }
+        }
+        _ => {
+            collect_in_node(&condition.then_block, document, values, diagnostics);
+            match &condition.else_block {
+                Some(Either::Left(else_condition)) => {
+                    collect_in_condition(else_condition, document, values, diagnostics);
+                }
+                Some(Either::Right(else_block)) => {
+                    collect_in_node(else_block.as_ref(), document, values, diagnostics);
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+fn collect_in_node<'i>(
+    node: &dyn Node<'i>,
+    document: &Document,
+    values: &KnownValues,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if let Some(Statement::Condition(condition)) = node.as_statement() {
+        collect_in_condition(condition, document, values, diagnostics);
+        return;
+    }
+    for child in node.children() {
+        collect_in_node(child, document, values, diagnostics);
+    }
+}
+
+/// Reports branches of conditions that are never taken with the build
+/// arguments of the active build directory and the assumed ones, which take
+/// precedence. Only open documents are reported, so that diagnostics
+/// published for the whole workspace are not flooded with hints.
+pub fn collect_inactive_branches(
+    file: &AnalyzedFile,
+    analyzer: &Analyzer,
+    assumed_args: &KnownValues,
+    request_time: Instant,
+) -> Vec<Diagnostic> {
+    if !matches!(file.document.version, DocumentVersion::InMemory { .. }) {
+        return Vec::new();
+    }
+    let Ok(analyzer) = analyzer.workspace_for(&file.workspace_root) else {
+        return Vec::new();
+    };
+
+    let mut values = analyzer.active_args(request_time);
+    values.extend(assumed_args.clone());
+
+    let mut diagnostics = Vec::new();
+    collect_in_node(
+        file.parsed_root.get(),
+        &file.document,
+        &values,
+        &mut diagnostics,
+    );
+    diagnostics
+}
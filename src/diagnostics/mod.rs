@@ -12,15 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::time::Instant;
+use std::{sync::Arc, time::Instant};
 
 use tower_lsp::lsp_types::Diagnostic;
 
 use crate::{
-    analyzer::{AnalyzedFile, Analyzer},
-    diagnostics::{syntax::collect_syntax_errors, undefined::collect_undefined_identifiers},
+    analyzer::{AnalyzedFile, Analyzer, KnownValues},
+    diagnostics::{
+        inactive::collect_inactive_branches, parameters::collect_missing_parameters,
+        syntax::collect_syntax_errors, undefined::collect_undefined_identifiers,
+    },
 };
 
+mod inactive;
+mod parameters;
 mod syntax;
 mod undefined;
 
@@ -32,13 +37,16 @@ pub struct DiagnosticDataUndefined {
 }
 
 pub fn compute_diagnostics(
-    file: &AnalyzedFile,
+    file: &Arc<AnalyzedFile>,
     analyzer: &Analyzer,
+    assumed_args: &KnownValues,
     request_time: Instant,
 ) -> Vec<Diagnostic> {
     [
         collect_syntax_errors(file.parsed_root.get(), &file.document),
         collect_undefined_identifiers(file, analyzer, request_time),
+        collect_inactive_branches(file, analyzer, assumed_args, request_time),
+        collect_missing_parameters(file, analyzer, request_time),
     ]
     .concat()
 }
@@ -0,0 +1,227 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+    time::Instant,
+};
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+
+use crate::{
+    analyzer::{
+        AnalyzedBlock, AnalyzedFile, AnalyzedStatement, AnalyzedTarget, Analyzer, TemplateMap,
+        TemplateParameter, TopLevelStatementsExt, VariableMap,
+    },
+    common::{builtins::SET_DEFAULTS, storage::Document, utils::parse_simple_literal},
+};
+
+/// Names of the variables assigned by `set_defaults` for each target type.
+type Defaults = HashMap<String, HashSet<String>>;
+
+/// Required parameters of templates, keyed by the file and position of the
+/// template definition.
+type RequiredParameters<'p> = HashMap<(&'p Path, usize), Vec<TemplateParameter<'p>>>;
+
+fn collect_defaults<'a>(files: impl IntoIterator<Item = &'a Arc<AnalyzedFile>>) -> Defaults {
+    let mut defaults = Defaults::new();
+    for file in files {
+        for statement in file.analyzed_root.get().all_statements() {
+            let AnalyzedStatement::BuiltinCall(builtin_call) = statement else {
+                continue;
+            };
+            if builtin_call.call.function.name != SET_DEFAULTS {
+                continue;
+            }
+            let (Some(name), Some(body_block)) = (
+                builtin_call
+                    .call
+                    .only_arg()
+                    .and_then(|expr| expr.as_simple_string()),
+                &builtin_call.body_block,
+            ) else {
+                continue;
+            };
+            defaults
+                .entry(name.to_string())
+                .or_default()
+                .extend(body_block.variables().into_keys().map(str::to_string));
+        }
+    }
+    defaults
+}
+
+fn check_target<'p>(
+    target: &AnalyzedTarget<'p>,
+    templates: &TemplateMap<'p>,
+    variables: &VariableMap<'p>,
+    defaults: &Defaults,
+    required_parameters: &mut RequiredParameters<'p>,
+    document: &Document,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(template) = templates.get(target.call.function.name) else {
+        return;
+    };
+
+    // Variables forwarded with a computed list are unknown, and so are those
+    // forwarded with "*" unless they are excluded.
+    let forwards: Vec<_> = target
+        .body_block
+        .top_level_statements()
+        .filter_map(|statement| match statement {
+            AnalyzedStatement::ForwardVariablesFrom(forward_variables_from) => {
+                Some(forward_variables_from)
+            }
+            _ => None,
+        })
+        .collect();
+    if forwards.iter().any(|forward_variables_from| {
+        !forward_variables_from.is_wildcard()
+            && forward_variables_from
+                .includes
+                .as_simple_string_list()
+                .is_none()
+    }) {
+        return;
+    }
+
+    let assigned = target.body_block.variables();
+    let defaulted = defaults.get(template.name);
+    let parameters = required_parameters
+        .entry((&template.document.path, template.call.span.start()))
+        .or_insert_with(|| {
+            template
+                .parameters()
+                .into_iter()
+                .filter(|parameter| parameter.required)
+                .collect()
+        });
+    for parameter in parameters.iter() {
+        if assigned.contains_key(parameter.name)
+            || defaulted.is_some_and(|names| names.contains(parameter.name))
+        {
+            continue;
+        }
+        if forwards.iter().any(|forward_variables_from| {
+            forward_variables_from.is_wildcard()
+                && !forward_variables_from
+                    .excluded_names(variables)
+                    .contains(&parameter.name)
+        }) {
+            continue;
+        }
+        let mut message = format!(
+            "Missing required parameter {} of {}",
+            parameter.name, template.name
+        );
+        if let Some(assertion_message) = parameter.assertion_message() {
+            message.push_str(": ");
+            message.push_str(parse_simple_literal(assertion_message).unwrap_or(assertion_message));
+        }
+        diagnostics.push(Diagnostic {
+            range: document.line_index.range(target.call.function.span),
+            severity: Some(DiagnosticSeverity::WARNING),
+            message,
+            ..Default::default()
+        });
+    }
+}
+
+/// Checks the targets in a block, with the templates visible in it. Templates
+/// defined in the block are added once before descending into its subscopes.
+fn collect_in_block<'p>(
+    block: &AnalyzedBlock<'p>,
+    templates: &TemplateMap<'p>,
+    variables: &VariableMap<'p>,
+    defaults: &Defaults,
+    required_parameters: &mut RequiredParameters<'p>,
+    document: &Document,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let local_templates: Vec<_> = block
+        .top_level_statements()
+        .filter_map(|statement| match statement {
+            AnalyzedStatement::Template(template) => template.as_template(block.document),
+            _ => None,
+        })
+        .collect();
+    let extended_templates;
+    let templates = if local_templates.is_empty() {
+        templates
+    } else {
+        let mut extended = templates.clone();
+        extended.extend(
+            local_templates
+                .into_iter()
+                .map(|template| (template.name, template)),
+        );
+        extended_templates = extended;
+        &extended_templates
+    };
+
+    for statement in block.top_level_statements() {
+        if let AnalyzedStatement::Target(target) = statement {
+            check_target(
+                target,
+                templates,
+                variables,
+                defaults,
+                required_parameters,
+                document,
+                diagnostics,
+            );
+        }
+        for scope in statement.subscopes() {
+            collect_in_block(
+                scope,
+                templates,
+                variables,
+                defaults,
+                required_parameters,
+                document,
+                diagnostics,
+            );
+        }
+    }
+}
+
+/// Reports template invocations whose blocks never assign parameters that the
+/// template asserts to be defined. Parameters assigned by `set_defaults` in
+/// the file or the files it imports are considered to be defined.
+pub fn collect_missing_parameters(
+    file: &Arc<AnalyzedFile>,
+    analyzer: &Analyzer,
+    request_time: Instant,
+) -> Vec<Diagnostic> {
+    let Ok(analyzer) = analyzer.workspace_for(&file.workspace_root) else {
+        return Vec::new();
+    };
+    let environment = analyzer.analyze_imports(file, request_time);
+    let defaults = collect_defaults(std::iter::once(file).chain(environment.files()));
+
+    let mut diagnostics = Vec::new();
+    collect_in_block(
+        file.analyzed_root.get(),
+        &environment.get().templates,
+        &environment.get().variables,
+        &defaults,
+        &mut RequiredParameters::new(),
+        &file.document,
+        &mut diagnostics,
+    );
+    diagnostics
+}
@@ -12,7 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashSet, sync::OnceLock, time::Instant};
+use std::{
+    collections::HashSet,
+    sync::{Arc, OnceLock},
+    time::Instant,
+};
 
 use either::Either;
 use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
@@ -20,7 +24,7 @@ use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
 use crate::{
     analyzer::{
         AnalyzedBlock, AnalyzedFile, AnalyzedStatement, Analyzer, TopLevelStatementsExt,
-        WorkspaceAnalyzer,
+        VariableMap, WorkspaceAnalyzer,
     },
     common::builtins::{BUILTINS, DEFINED, IMPORT},
     diagnostics::{DiagnosticDataUndefined, DIAGNOSTIC_CODE_UNDEFINED},
@@ -46,7 +50,10 @@ fn builtin_scope() -> HashSet<String> {
 #[derive(Clone)]
 enum EnvironmentTracker {
     Ok(HashSet<String>),
-    Untrackable,
+    /// Any variable may be defined, except the excluded ones.
+    Untrackable {
+        excluded: HashSet<String>,
+    },
 }
 
 impl EnvironmentTracker {
@@ -57,7 +64,7 @@ impl EnvironmentTracker {
     pub fn may_contain(&self, name: &str) -> bool {
         match self {
             EnvironmentTracker::Ok(env) => env.contains(name),
-            EnvironmentTracker::Untrackable => true,
+            EnvironmentTracker::Untrackable { excluded } => !excluded.contains(name),
         }
     }
 
@@ -66,12 +73,34 @@ impl EnvironmentTracker {
             EnvironmentTracker::Ok(env) => {
                 env.insert(name.to_string());
             }
-            EnvironmentTracker::Untrackable => {}
+            EnvironmentTracker::Untrackable { excluded } => {
+                excluded.remove(name);
+            }
         }
     }
 
     pub fn set_untrackable(&mut self) {
-        *self = EnvironmentTracker::Untrackable;
+        *self = EnvironmentTracker::Untrackable {
+            excluded: HashSet::new(),
+        };
+    }
+
+    /// Makes any variable possibly defined, except the excluded ones that
+    /// are not defined yet.
+    pub fn set_untrackable_except(&mut self, names: &[&str]) {
+        match self {
+            EnvironmentTracker::Ok(env) => {
+                let excluded = names
+                    .iter()
+                    .filter(|name| !env.contains(**name))
+                    .map(|name| name.to_string())
+                    .collect();
+                *self = EnvironmentTracker::Untrackable { excluded };
+            }
+            EnvironmentTracker::Untrackable { excluded } => {
+                excluded.retain(|name| names.contains(&name.as_str()));
+            }
+        }
     }
 }
 
@@ -79,7 +108,11 @@ impl<'s> Extend<&'s str> for EnvironmentTracker {
     fn extend<T: IntoIterator<Item = &'s str>>(&mut self, iter: T) {
         match self {
             EnvironmentTracker::Ok(env) => env.extend(iter.into_iter().map(|s| s.to_string())),
-            EnvironmentTracker::Untrackable => {}
+            EnvironmentTracker::Untrackable { excluded } => {
+                for name in iter {
+                    excluded.remove(name);
+                }
+            }
         }
     }
 }
@@ -117,6 +150,7 @@ impl<'p> PrimaryExpr<'p> {
         file: &'p AnalyzedFile,
         analyzer: &WorkspaceAnalyzer,
         request_time: Instant,
+        variables: &VariableMap,
         tracker: &mut EnvironmentTracker,
         diagnostics: &mut Vec<Diagnostic>,
     ) {
@@ -142,6 +176,7 @@ impl<'p> PrimaryExpr<'p> {
                         file,
                         analyzer,
                         request_time,
+                        variables,
                         tracker,
                         diagnostics,
                     );
@@ -155,6 +190,7 @@ impl<'p> PrimaryExpr<'p> {
                     file,
                     analyzer,
                     request_time,
+                    variables,
                     tracker,
                     diagnostics,
                 );
@@ -169,6 +205,7 @@ impl<'p> PrimaryExpr<'p> {
                     file,
                     analyzer,
                     request_time,
+                    variables,
                     tracker,
                     diagnostics,
                 );
@@ -179,6 +216,7 @@ impl<'p> PrimaryExpr<'p> {
                         file,
                         analyzer,
                         request_time,
+                        variables,
                         tracker,
                         diagnostics,
                     );
@@ -198,6 +236,7 @@ impl<'p> Expr<'p> {
         file: &'p AnalyzedFile,
         analyzer: &WorkspaceAnalyzer,
         request_time: Instant,
+        variables: &VariableMap,
         tracker: &mut EnvironmentTracker,
         diagnostics: &mut Vec<Diagnostic>,
     ) {
@@ -207,6 +246,7 @@ impl<'p> Expr<'p> {
                     file,
                     analyzer,
                     request_time,
+                    variables,
                     tracker,
                     diagnostics,
                 );
@@ -216,6 +256,7 @@ impl<'p> Expr<'p> {
                     file,
                     analyzer,
                     request_time,
+                    variables,
                     tracker,
                     diagnostics,
                 );
@@ -225,6 +266,7 @@ impl<'p> Expr<'p> {
                     file,
                     analyzer,
                     request_time,
+                    variables,
                     tracker,
                     diagnostics,
                 );
@@ -232,6 +274,7 @@ impl<'p> Expr<'p> {
                     file,
                     analyzer,
                     request_time,
+                    variables,
                     tracker,
                     diagnostics,
                 );
@@ -246,6 +289,7 @@ impl<'p> AnalyzedBlock<'p> {
         file: &AnalyzedFile,
         analyzer: &WorkspaceAnalyzer,
         request_time: Instant,
+        variables: &VariableMap,
         tracker: &mut EnvironmentTracker,
         diagnostics: &mut Vec<Diagnostic>,
     ) {
@@ -258,6 +302,7 @@ impl<'p> AnalyzedBlock<'p> {
                             file,
                             analyzer,
                             request_time,
+                            variables,
                             tracker,
                             diagnostics,
                         );
@@ -266,6 +311,7 @@ impl<'p> AnalyzedBlock<'p> {
                         file,
                         analyzer,
                         request_time,
+                        variables,
                         tracker,
                         diagnostics,
                     );
@@ -280,6 +326,7 @@ impl<'p> AnalyzedBlock<'p> {
                                 file,
                                 analyzer,
                                 request_time,
+                                variables,
                                 tracker,
                                 diagnostics,
                             );
@@ -296,6 +343,7 @@ impl<'p> AnalyzedBlock<'p> {
                         file,
                         analyzer,
                         request_time,
+                        variables,
                         tracker,
                         diagnostics,
                     );
@@ -306,6 +354,7 @@ impl<'p> AnalyzedBlock<'p> {
                             file,
                             analyzer,
                             request_time,
+                            variables,
                             tracker,
                             diagnostics,
                         );
@@ -317,6 +366,7 @@ impl<'p> AnalyzedBlock<'p> {
                             file,
                             analyzer,
                             request_time,
+                            variables,
                             tracker,
                             diagnostics,
                         );
@@ -328,6 +378,7 @@ impl<'p> AnalyzedBlock<'p> {
                             file,
                             analyzer,
                             request_time,
+                            variables,
                             tracker,
                             diagnostics,
                         );
@@ -344,6 +395,7 @@ impl<'p> AnalyzedBlock<'p> {
                             file,
                             analyzer,
                             request_time,
+                            variables,
                             tracker,
                             diagnostics,
                         );
@@ -360,6 +412,7 @@ impl<'p> AnalyzedBlock<'p> {
                     file,
                     analyzer,
                     request_time,
+                    variables,
                     &mut tracker.clone(),
                     diagnostics,
                 );
@@ -376,10 +429,16 @@ impl<'p> AnalyzedBlock<'p> {
                     tracker.insert(foreach.loop_variable.name);
                 }
                 AnalyzedStatement::ForwardVariablesFrom(forward_variables_from) => {
-                    if let Some(includes) = forward_variables_from.includes.as_simple_string_list()
+                    let excluded_names = forward_variables_from.excluded_names(variables);
+                    if forward_variables_from.is_wildcard() {
+                        tracker.set_untrackable_except(&excluded_names);
+                    } else if let Some(includes) =
+                        forward_variables_from.includes.as_simple_string_list()
                     {
                         for include in includes {
-                            tracker.insert(include);
+                            if !excluded_names.contains(&include) {
+                                tracker.insert(include);
+                            }
                         }
                     } else {
                         tracker.set_untrackable();
@@ -409,7 +468,7 @@ impl<'p> AnalyzedBlock<'p> {
 }
 
 pub fn collect_undefined_identifiers(
-    file: &AnalyzedFile,
+    file: &Arc<AnalyzedFile>,
     analyzer: &Analyzer,
     request_time: Instant,
 ) -> Vec<Diagnostic> {
@@ -423,11 +482,16 @@ pub fn collect_undefined_identifiers(
     let environment = analyzer.analyze_files(&build_config, request_time);
     tracker.extend(environment.get().variables.keys().copied());
 
+    // Variables visible through imports, used to resolve identifier lists of
+    // excludes in forward_variables_from.
+    let imports = analyzer.analyze_imports(file, request_time);
+
     let mut diagnostics: Vec<Diagnostic> = Vec::new();
     file.analyzed_root.get().collect_undefined_identifiers(
         file,
         &analyzer,
         request_time,
+        &imports.get().variables,
         &mut tracker,
         &mut diagnostics,
     );
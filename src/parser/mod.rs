@@ -44,6 +44,18 @@ pub trait Node<'i> {
         None
     }
 
+    fn as_call(&self) -> Option<&Call<'i>> {
+        None
+    }
+
+    fn as_list(&self) -> Option<&ListLiteral<'i>> {
+        None
+    }
+
+    fn as_scope_access(&self) -> Option<&ScopeAccess<'i>> {
+        None
+    }
+
     fn as_error<'n>(&'n self) -> Option<ErrorRef<'i, 'n>> {
         None
     }
@@ -60,6 +72,10 @@ pub trait Node<'i> {
         FilterWalk::new(self.as_node(), |node| node.as_string())
     }
 
+    fn calls<'n>(&'n self) -> FilterWalk<'i, 'n, &'n Call<'i>> {
+        FilterWalk::new(self.as_node(), |node| node.as_call())
+    }
+
     fn errors<'n>(&'n self) -> FilterWalk<'i, 'n, ErrorRef<'i, 'n>> {
         FilterWalk::new(self.as_node(), |node| node.as_error())
     }
@@ -240,6 +256,10 @@ impl<'i> Node<'i> for Call<'i> {
     fn span(&self) -> Span<'i> {
         self.span
     }
+
+    fn as_call(&self) -> Option<&Call<'i>> {
+        Some(self)
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -364,6 +384,10 @@ impl<'i> Node<'i> for ScopeAccess<'i> {
     fn span(&self) -> Span<'i> {
         self.span
     }
+
+    fn as_scope_access(&self) -> Option<&ScopeAccess<'i>> {
+        Some(self)
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -623,6 +647,11 @@ pub struct StringLiteral<'i> {
     pub span: Span<'i>,
 }
 
+/// Returns the span of a string literal without the quotes.
+pub(crate) fn string_content_span(span: Span<'_>) -> Span<'_> {
+    Span::new(span.get_input(), span.start() + 1, span.end() - 1).unwrap()
+}
+
 impl<'i> Node<'i> for StringLiteral<'i> {
     fn as_node(&self) -> &dyn Node<'i> {
         self
@@ -682,6 +711,10 @@ impl<'i> Node<'i> for ListLiteral<'i> {
     fn span(&self) -> Span<'i> {
         self.span
     }
+
+    fn as_list(&self) -> Option<&ListLiteral<'i>> {
+        Some(self)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -168,15 +168,16 @@ fn convert_expr(pair: Pair<Rule>) -> Expr {
 
     // TODO: Cache PrattParser
     let pratt_parser = PrattParser::new()
-        .op(Op::prefix(Rule::not))
-        .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
+        // Operators are listed from the lowest precedence.
+        .op(Op::infix(Rule::or, Assoc::Left))
+        .op(Op::infix(Rule::and, Assoc::Left))
+        .op(Op::infix(Rule::eq, Assoc::Left) | Op::infix(Rule::ne, Assoc::Left))
         .op(Op::infix(Rule::ge, Assoc::Left)
             | Op::infix(Rule::gt, Assoc::Left)
             | Op::infix(Rule::le, Assoc::Left)
             | Op::infix(Rule::lt, Assoc::Left))
-        .op(Op::infix(Rule::eq, Assoc::Left) | Op::infix(Rule::ne, Assoc::Left))
-        .op(Op::infix(Rule::and, Assoc::Left))
-        .op(Op::infix(Rule::or, Assoc::Left));
+        .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
+        .op(Op::prefix(Rule::not));
     pratt_parser
         .map_primary(|pair| Expr::Primary(Box::new(convert_primary(pair))))
         .map_prefix(|op, rhs| {
@@ -14,7 +14,7 @@
 
 #![cfg(test)]
 
-use super::{parse, Node};
+use super::{parse, BinaryOp, Expr, Node, Statement};
 
 fn parse_no_errors(input: &str) {
     let block = parse(input);
@@ -103,3 +103,41 @@ fn missing_comma() {
         .collect();
     assert_eq!(errors, [(9, 9)]);
 }
+
+#[test]
+fn operator_precedence() {
+    let block = parse("a = !b || c == 1 + 2 && d");
+    let Some(Statement::Assignment(assignment)) = block.statements.first() else {
+        panic!("not an assignment");
+    };
+    let Expr::Binary(or) = assignment.rvalue.as_ref() else {
+        panic!("not a binary expression");
+    };
+    assert_eq!(or.op, BinaryOp::Or);
+    assert!(matches!(or.lhs.as_ref(), Expr::Unary(_)));
+    let Expr::Binary(and) = or.rhs.as_ref() else {
+        panic!("not a binary expression");
+    };
+    assert_eq!(and.op, BinaryOp::And);
+    let Expr::Binary(eq) = and.lhs.as_ref() else {
+        panic!("not a binary expression");
+    };
+    assert_eq!(eq.op, BinaryOp::Eq);
+    assert!(matches!(eq.rhs.as_ref(), Expr::Binary(add) if add.op == BinaryOp::Add));
+}
+
+#[test]
+fn not_binds_tighter_than_and_or() {
+    let block = parse("a = !b || c && d");
+    let Some(Statement::Assignment(assignment)) = block.statements.first() else {
+        panic!("not an assignment");
+    };
+    let Expr::Binary(or) = assignment.rvalue.as_ref() else {
+        panic!("not a binary expression");
+    };
+    assert_eq!(or.op, BinaryOp::Or);
+    assert!(
+        matches!(or.lhs.as_ref(), Expr::Unary(not) if not.expr.as_primary_identifier().is_some())
+    );
+    assert!(matches!(or.rhs.as_ref(), Expr::Binary(and) if and.op == BinaryOp::And));
+}
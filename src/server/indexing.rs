@@ -0,0 +1,114 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use tokio::sync::mpsc::UnboundedReceiver;
+use tower_lsp::lsp_types::{
+    NumberOrString, ProgressToken, WorkDoneProgress, WorkDoneProgressBegin, WorkDoneProgressEnd,
+    WorkDoneProgressReport,
+};
+
+use crate::{
+    analyzer::IndexingProgress,
+    server::{providers::diagnostics::notify_diagnostics_changed, RequestContext},
+};
+
+/// Spawns a task handling background indexing events. It reports progress to
+/// the client as work done progress, one progress token per workspace, and
+/// updates workspace diagnostics once a workspace is indexed.
+pub fn spawn_indexing_listener(
+    context: RequestContext,
+    mut receiver: UnboundedReceiver<IndexingProgress>,
+    report_progress: bool,
+) {
+    let client = context.client.clone();
+
+    tokio::spawn(async move {
+        let mut tokens: HashMap<_, ProgressToken> = HashMap::new();
+        while let Some(event) = receiver.recv().await {
+            match event {
+                IndexingProgress::Begin { root } => {
+                    if !report_progress {
+                        continue;
+                    }
+                    let token =
+                        NumberOrString::String(format!("gn-indexing:{}", root.to_string_lossy()));
+                    if client
+                        .create_work_done_progress(token.clone())
+                        .await
+                        .is_err()
+                    {
+                        continue;
+                    }
+                    client
+                        .work_done_progress(
+                            token.clone(),
+                            WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                                title: format!("Indexing {}", root.display()),
+                                cancellable: Some(false),
+                                message: Some("Scanning files".to_string()),
+                                percentage: Some(0),
+                            }),
+                        )
+                        .await;
+                    tokens.insert(root, token);
+                }
+                IndexingProgress::Report {
+                    root,
+                    processed,
+                    total,
+                } => {
+                    let Some(token) = tokens.get(&root) else {
+                        continue;
+                    };
+                    client
+                        .work_done_progress(
+                            token.clone(),
+                            WorkDoneProgress::Report(WorkDoneProgressReport {
+                                cancellable: Some(false),
+                                message: Some(format!("{processed}/{total} files")),
+                                percentage: Some((processed * 100 / total.max(1)) as u32),
+                            }),
+                        )
+                        .await;
+                }
+                IndexingProgress::End {
+                    root,
+                    processed,
+                    elapsed,
+                } => {
+                    // Newly indexed files may be in the scope of workspace
+                    // diagnostics.
+                    notify_diagnostics_changed(&context);
+
+                    let Some(token) = tokens.remove(&root) else {
+                        continue;
+                    };
+                    client
+                        .work_done_progress(
+                            token,
+                            WorkDoneProgress::End(WorkDoneProgressEnd {
+                                message: Some(format!(
+                                    "Processed {processed} files in {:.1}s",
+                                    elapsed.as_secs_f64()
+                                )),
+                            }),
+                        )
+                        .await;
+                }
+            }
+        }
+    });
+}
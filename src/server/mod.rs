@@ -15,22 +15,42 @@
 #[cfg(test)]
 use std::path::Path;
 use std::{
+    path::PathBuf,
     sync::{Arc, Mutex, OnceLock},
     time::Instant,
 };
 
+use serde_json::Value;
+use tokio::sync::{mpsc::unbounded_channel, watch};
 use tower_lsp::{
     lsp_types::{
-        CodeActionKind, CodeActionOptions, CodeActionParams, CodeActionProviderCapability,
-        CodeActionResponse, CodeLens, CodeLensOptions, CodeLensParams, CompletionOptions,
-        CompletionParams, CompletionResponse, DidChangeConfigurationParams,
-        DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
-        DocumentFormattingParams, DocumentLink, DocumentLinkOptions, DocumentLinkParams,
-        DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse,
-        Hover, HoverParams, HoverProviderCapability, InitializeParams, InitializeResult,
-        InitializedParams, Location, MessageType, OneOf, ReferenceParams, ServerCapabilities,
-        SymbolInformation, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
-        WorkspaceSymbolParams,
+        request::{GotoDeclarationParams, GotoDeclarationResponse},
+        CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+        CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+        CallHierarchyServerCapability, ClientCapabilities, CodeActionKind, CodeActionOptions,
+        CodeActionParams, CodeActionProviderCapability, CodeActionResponse, CodeLens,
+        CodeLensOptions, CodeLensParams, CompletionOptions, CompletionParams, CompletionResponse,
+        DeclarationCapability, DiagnosticOptions, DiagnosticServerCapabilities,
+        DidChangeConfigurationParams, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+        DidChangeWatchedFilesRegistrationOptions, DidChangeWorkspaceFoldersParams,
+        DidCloseTextDocumentParams, DidOpenTextDocumentParams, DocumentDiagnosticParams,
+        DocumentDiagnosticReportResult, DocumentFormattingParams, DocumentHighlight,
+        DocumentHighlightParams, DocumentLink, DocumentLinkOptions, DocumentLinkParams,
+        DocumentOnTypeFormattingOptions, DocumentOnTypeFormattingParams,
+        DocumentRangeFormattingParams, DocumentSymbolParams, DocumentSymbolResponse,
+        ExecuteCommandOptions, ExecuteCommandParams, FileSystemWatcher, FoldingRange,
+        FoldingRangeParams, FoldingRangeProviderCapability, GlobPattern, GotoDefinitionParams,
+        GotoDefinitionResponse, Hover, HoverParams, HoverProviderCapability, InitializeParams,
+        InitializeResult, InitializedParams, InlayHint, InlayHintParams, Location, MessageType,
+        OneOf, PrepareRenameResponse, ReferenceParams, Registration, RenameOptions, RenameParams,
+        SelectionRange, SelectionRangeParams, SelectionRangeProviderCapability,
+        SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensParams,
+        SemanticTokensRangeParams, SemanticTokensRangeResult, SemanticTokensResult,
+        SemanticTokensServerCapabilities, ServerCapabilities, SignatureHelp, SignatureHelpOptions,
+        SignatureHelpParams, SymbolInformation, TextDocumentPositionParams,
+        TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url, WorkspaceDiagnosticParams,
+        WorkspaceDiagnosticReportResult, WorkspaceEdit, WorkspaceFoldersServerCapabilities,
+        WorkspaceServerCapabilities, WorkspaceSymbolParams,
     },
     LanguageServer, LspService, Server,
 };
@@ -41,9 +61,11 @@ use crate::{
         client::TestableClient, error::RpcResult, storage::DocumentStorage,
         workspace::WorkspaceFinder,
     },
+    server::providers::build_dir::{BuildDirsParams, BuildDirsResult, SetActiveBuildDirParams},
 };
 
 mod imports;
+mod indexing;
 mod providers;
 mod symbols;
 
@@ -56,7 +78,9 @@ struct ServerContext {
     pub storage: Arc<Mutex<DocumentStorage>>,
     pub analyzer: OnceLock<Arc<Analyzer>>,
     pub options: OnceLock<Arc<InitializationOptions>>,
+    pub client_capabilities: OnceLock<Arc<ClientCapabilities>>,
     pub client: TestableClient,
+    pub diagnostics_changes: Arc<watch::Sender<()>>,
 }
 
 impl ServerContext {
@@ -65,7 +89,9 @@ impl ServerContext {
             storage,
             analyzer: OnceLock::new(),
             options: OnceLock::new(),
+            client_capabilities: OnceLock::new(),
             client,
+            diagnostics_changes: Arc::new(watch::Sender::new(())),
         }
     }
 
@@ -79,17 +105,22 @@ impl ServerContext {
             &storage,
             WorkspaceFinder::new(client_root),
             IndexingLevel::Disabled,
+            None,
         )));
         let options = OnceLock::new();
         let _ = options.set(Arc::new(InitializationOptions {
             vscode_extension: true,
         }));
+        let client_capabilities = OnceLock::new();
+        let _ = client_capabilities.set(Arc::new(ClientCapabilities::default()));
 
         Self {
             storage,
             analyzer,
             options,
+            client_capabilities,
             client: TestableClient::new_for_testing(),
+            diagnostics_changes: Arc::new(watch::Sender::new(())),
         }
     }
 
@@ -98,7 +129,9 @@ impl ServerContext {
             storage: self.storage.clone(),
             analyzer: self.analyzer.get().unwrap().clone(),
             options: self.options.get().unwrap().clone(),
+            client_capabilities: self.client_capabilities.get().unwrap().clone(),
             client: self.client.clone(),
+            diagnostics_changes: self.diagnostics_changes.clone(),
             request_time: Instant::now(),
         }
     }
@@ -109,7 +142,10 @@ pub struct RequestContext {
     pub storage: Arc<Mutex<DocumentStorage>>,
     pub analyzer: Arc<Analyzer>,
     pub options: Arc<InitializationOptions>,
+    pub client_capabilities: Arc<ClientCapabilities>,
     pub client: TestableClient,
+    /// Notified when diagnostics of any file may have changed.
+    pub diagnostics_changes: Arc<watch::Sender<()>>,
     pub request_time: Instant,
 }
 
@@ -120,6 +156,41 @@ impl RequestContext {
     }
 }
 
+/// Registers watchers for files that may change outside the editor. Files in
+/// the client roots are no longer verified periodically once registered.
+async fn watch_files(context: &RequestContext) {
+    let watchers = ["**/*.gn", "**/*.gni", "**/.gn"]
+        .into_iter()
+        .map(|pattern| FileSystemWatcher {
+            glob_pattern: GlobPattern::String(pattern.to_string()),
+            kind: None,
+        })
+        .collect();
+    let registration = Registration {
+        id: "gn-watched-files".to_string(),
+        method: "workspace/didChangeWatchedFiles".to_string(),
+        register_options: serde_json::to_value(DidChangeWatchedFilesRegistrationOptions {
+            watchers,
+        })
+        .ok(),
+    };
+    match context.client.register_capability(vec![registration]).await {
+        Ok(()) => {
+            let roots = context.analyzer.workspace_finder().client_roots();
+            context.storage.lock().unwrap().set_watched_roots(roots);
+        }
+        Err(err) => {
+            context
+                .client
+                .log_message(
+                    MessageType::WARNING,
+                    format!("Failed to watch files: {err}"),
+                )
+                .await;
+        }
+    }
+}
+
 struct Backend {
     context: ServerContext,
 }
@@ -130,56 +201,141 @@ impl Backend {
             context: ServerContext::new(storage, client),
         }
     }
+
+    async fn build_dirs(&self, params: BuildDirsParams) -> RpcResult<BuildDirsResult> {
+        Ok(providers::build_dir::build_dirs(&self.context.request(), params).await?)
+    }
+
+    async fn set_active_build_dir(&self, params: SetActiveBuildDirParams) -> RpcResult<()> {
+        Ok(providers::build_dir::set_active_build_dir(&self.context.request(), params).await?)
+    }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
     async fn initialize(&self, params: InitializeParams) -> RpcResult<InitializeResult> {
-        let finder = WorkspaceFinder::new(
-            params
+        let client_roots: Vec<PathBuf> = match &params.workspace_folders {
+            Some(folders) => folders
+                .iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect(),
+            None => params
                 .root_uri
-                .and_then(|root_uri| root_uri.to_file_path().ok())
-                .as_deref(),
-        );
+                .iter()
+                .filter_map(|root_uri| root_uri.to_file_path().ok())
+                .collect(),
+        };
+        let finder = WorkspaceFinder::new(client_roots.iter().map(PathBuf::as_path));
         let configurations = self.context.client.configurations().await;
+        let supports_progress = params
+            .capabilities
+            .window
+            .as_ref()
+            .and_then(|window| window.work_done_progress)
+            .unwrap_or(false);
+        let (indexing_sender, indexing_receiver) = unbounded_channel();
         let analyzer = Arc::new(Analyzer::new(
             &self.context.storage,
             finder,
             configurations.indexing_level(),
+            Some(indexing_sender),
         ));
+        analyzer.set_build_dir_setting(configurations.active_build_dir.clone());
         self.context.analyzer.set(analyzer).ok();
 
         let options: InitializationOptions = params
             .initialization_options
             .and_then(|value| serde_json::from_value(value).ok())
             .unwrap_or_default();
+        let vscode_extension = options.vscode_extension;
         self.context.options.set(Arc::new(options)).ok();
+        self.context
+            .client_capabilities
+            .set(Arc::new(params.capabilities))
+            .ok();
+
+        indexing::spawn_indexing_listener(
+            self.context.request(),
+            indexing_receiver,
+            supports_progress,
+        );
+        providers::diagnostics::spawn_workspace_diagnostics_publisher(self.context.request());
 
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
                 )),
+                declaration_provider: Some(DeclarationCapability::Simple(true)),
                 definition_provider: Some(OneOf::Left(true)),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
                 document_link_provider: Some(DocumentLinkOptions {
                     resolve_provider: Some(true),
                     work_done_progress_options: Default::default(),
                 }),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                document_highlight_provider: Some(OneOf::Left(true)),
                 completion_provider: Some(CompletionOptions::default()),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
                 document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
+                document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                    first_trigger_character: "}".to_string(),
+                    more_trigger_character: Some(vec!["\n".to_string()]),
+                }),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+                diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
+                    DiagnosticOptions {
+                        identifier: Some("gn".to_string()),
+                        inter_file_dependencies: true,
+                        workspace_diagnostics: true,
+                        work_done_progress_options: Default::default(),
+                    },
+                )),
                 references_provider: Some(OneOf::Left(true)),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                })),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
                 code_lens_provider: Some(CodeLensOptions {
                     resolve_provider: Some(true),
                 }),
+                // The VSCode extension implements the commands by itself.
+                execute_command_provider: (!vscode_extension).then(|| ExecuteCommandOptions {
+                    commands: providers::execute_command::server_commands(),
+                    work_done_progress_options: Default::default(),
+                }),
                 code_action_provider: Some(CodeActionProviderCapability::Options(
                     CodeActionOptions {
                         code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
                         ..Default::default()
                     },
                 )),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            legend: providers::semantic_tokens::semantic_tokens_legend(),
+                            range: Some(true),
+                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            work_done_progress_options: Default::default(),
+                        },
+                    ),
+                ),
                 ..Default::default()
             },
             ..Default::default()
@@ -193,6 +349,19 @@ impl LanguageServer for Backend {
             .log_message(MessageType::INFO, "GN language server initialized")
             .await;
 
+        // Watch files to pick up changes made outside the editor, e.g. by git.
+        // Without dynamic registration, caches are verified periodically.
+        if context
+            .client_capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+            .and_then(|capability| capability.dynamic_registration)
+            == Some(true)
+        {
+            watch_files(&context).await;
+        }
+
         let configurations = self.context.client.configurations().await;
         if !configurations.background_indexing {
             return;
@@ -217,6 +386,10 @@ impl LanguageServer for Backend {
         providers::document::did_change(&self.context.request(), params).await;
     }
 
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        providers::document::did_change_watched_files(&self.context.request(), params).await;
+    }
+
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         providers::document::did_close(&self.context.request(), params).await;
     }
@@ -225,6 +398,34 @@ impl LanguageServer for Backend {
         providers::configuration::did_change_configuration(&self.context.request(), params).await;
     }
 
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        let context = self.context.request();
+        for folder in params.event.removed {
+            if let Ok(path) = folder.uri.to_file_path() {
+                context.analyzer.remove_client_root(&path);
+            }
+        }
+        for folder in params.event.added {
+            if let Ok(path) = folder.uri.to_file_path() {
+                context.analyzer.add_client_root(&path);
+            }
+        }
+        {
+            let mut storage = context.storage.lock().unwrap();
+            if storage.is_watching() {
+                storage.set_watched_roots(context.analyzer.workspace_finder().client_roots());
+            }
+        }
+        providers::diagnostics::notify_diagnostics_changed(&context);
+    }
+
+    async fn goto_declaration(
+        &self,
+        params: GotoDeclarationParams,
+    ) -> RpcResult<Option<GotoDeclarationResponse>> {
+        Ok(providers::declaration::goto_declaration(&self.context.request(), params).await?)
+    }
+
     async fn goto_definition(
         &self,
         params: GotoDefinitionParams,
@@ -236,6 +437,10 @@ impl LanguageServer for Backend {
         Ok(providers::hover::hover(&self.context.request(), params).await?)
     }
 
+    async fn inlay_hint(&self, params: InlayHintParams) -> RpcResult<Option<Vec<InlayHint>>> {
+        Ok(providers::inlay_hint::inlay_hint(&self.context.request(), params).await?)
+    }
+
     async fn document_link(
         &self,
         params: DocumentLinkParams,
@@ -254,14 +459,98 @@ impl LanguageServer for Backend {
         Ok(providers::document_symbol::document_symbol(&self.context.request(), params).await?)
     }
 
+    async fn document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> RpcResult<Option<Vec<DocumentHighlight>>> {
+        Ok(
+            providers::document_highlight::document_highlight(&self.context.request(), params)
+                .await?,
+        )
+    }
+
+    async fn folding_range(
+        &self,
+        params: FoldingRangeParams,
+    ) -> RpcResult<Option<Vec<FoldingRange>>> {
+        Ok(providers::folding_range::folding_range(&self.context.request(), params).await?)
+    }
+
+    async fn selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> RpcResult<Option<Vec<SelectionRange>>> {
+        Ok(providers::selection_range::selection_range(&self.context.request(), params).await?)
+    }
+
     async fn completion(&self, params: CompletionParams) -> RpcResult<Option<CompletionResponse>> {
         Ok(providers::completion::completion(&self.context.request(), params).await?)
     }
 
+    async fn signature_help(
+        &self,
+        params: SignatureHelpParams,
+    ) -> RpcResult<Option<SignatureHelp>> {
+        Ok(providers::signature_help::signature_help(&self.context.request(), params).await?)
+    }
+
     async fn references(&self, params: ReferenceParams) -> RpcResult<Option<Vec<Location>>> {
         Ok(providers::references::references(&self.context.request(), params).await?)
     }
 
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> RpcResult<Option<Vec<CallHierarchyItem>>> {
+        Ok(
+            providers::call_hierarchy::prepare_call_hierarchy(&self.context.request(), params)
+                .await?,
+        )
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> RpcResult<Option<Vec<CallHierarchyIncomingCall>>> {
+        Ok(providers::call_hierarchy::incoming_calls(&self.context.request(), params).await?)
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> RpcResult<Option<Vec<CallHierarchyOutgoingCall>>> {
+        Ok(providers::call_hierarchy::outgoing_calls(&self.context.request(), params).await?)
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> RpcResult<Option<PrepareRenameResponse>> {
+        Ok(providers::rename::prepare_rename(&self.context.request(), params).await?)
+    }
+
+    async fn rename(&self, params: RenameParams) -> RpcResult<Option<WorkspaceEdit>> {
+        Ok(providers::rename::rename(&self.context.request(), params).await?)
+    }
+
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> RpcResult<DocumentDiagnosticReportResult> {
+        Ok(providers::diagnostics::document_diagnostic(&self.context.request(), params).await?)
+    }
+
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> RpcResult<WorkspaceDiagnosticReportResult> {
+        Ok(providers::diagnostics::workspace_diagnostic(&self.context.request(), params).await?)
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> RpcResult<Option<Value>> {
+        Ok(providers::execute_command::execute_command(&self.context.request(), params).await?)
+    }
+
     async fn formatting(
         &self,
         params: DocumentFormattingParams,
@@ -269,6 +558,20 @@ impl LanguageServer for Backend {
         Ok(providers::formatting::formatting(&self.context.request(), params).await?)
     }
 
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> RpcResult<Option<Vec<TextEdit>>> {
+        Ok(providers::formatting::range_formatting(&self.context.request(), params).await?)
+    }
+
+    async fn on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> RpcResult<Option<Vec<TextEdit>>> {
+        Ok(providers::formatting::on_type_formatting(&self.context.request(), params).await?)
+    }
+
     async fn symbol(
         &self,
         params: WorkspaceSymbolParams,
@@ -287,12 +590,38 @@ impl LanguageServer for Backend {
     async fn code_action(&self, params: CodeActionParams) -> RpcResult<Option<CodeActionResponse>> {
         Ok(providers::code_action::code_action(&self.context.request(), params).await?)
     }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> RpcResult<Option<SemanticTokensResult>> {
+        Ok(
+            providers::semantic_tokens::semantic_tokens_full(&self.context.request(), params)
+                .await?,
+        )
+    }
+
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> RpcResult<Option<SemanticTokensRangeResult>> {
+        Ok(
+            providers::semantic_tokens::semantic_tokens_range(&self.context.request(), params)
+                .await?,
+        )
+    }
 }
 
 pub async fn run() {
     let storage = Arc::new(Mutex::new(DocumentStorage::new()));
     let (service, socket) =
-        LspService::new(move |client| Backend::new(storage, TestableClient::new(client)));
+        LspService::build(move |client| Backend::new(storage, TestableClient::new(client)))
+            .custom_method(providers::build_dir::BUILD_DIRS, Backend::build_dirs)
+            .custom_method(
+                providers::build_dir::SET_ACTIVE_BUILD_DIR,
+                Backend::set_active_build_dir,
+            )
+            .finish();
 
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
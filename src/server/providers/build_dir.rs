@@ -0,0 +1,148 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+
+use crate::{
+    common::error::{Error, Result},
+    server::{
+        providers::{diagnostics::refresh_diagnostics, utils::get_text_document_path},
+        RequestContext,
+    },
+};
+
+pub const BUILD_DIRS: &str = "gn/buildDirs";
+pub const SET_ACTIVE_BUILD_DIR: &str = "gn/setActiveBuildDir";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildDirsParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildDirsResult {
+    pub build_dirs: Vec<PathBuf>,
+    pub active_build_dir: Option<PathBuf>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetActiveBuildDirParams {
+    pub text_document: TextDocumentIdentifier,
+    pub build_dir: Option<PathBuf>,
+}
+
+/// Lists build directories of the workspace containing the document.
+pub async fn build_dirs(
+    context: &RequestContext,
+    params: BuildDirsParams,
+) -> Result<BuildDirsResult> {
+    let path = get_text_document_path(&params.text_document)?;
+    let workspace = context.analyzer.workspace_for(&path)?;
+    Ok(BuildDirsResult {
+        build_dirs: workspace.output_dirs(),
+        active_build_dir: workspace.active_build_dir(),
+    })
+}
+
+/// Switches the build directory of the workspace containing the document, or
+/// stops reading `args.gn` if none is given.
+pub async fn set_active_build_dir(
+    context: &RequestContext,
+    params: SetActiveBuildDirParams,
+) -> Result<()> {
+    let path = get_text_document_path(&params.text_document)?;
+    let workspace = context.analyzer.workspace_for(&path)?;
+    let build_dir = params
+        .build_dir
+        .map(|build_dir| workspace.context().root.join(build_dir));
+    if let Some(build_dir) = &build_dir {
+        if !build_dir.join("args.gn").exists() {
+            return Err(Error::General(format!(
+                "{} is not a build directory",
+                build_dir.display()
+            )));
+        }
+    }
+    workspace.set_active_build_dir(build_dir);
+    refresh_diagnostics(context).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Url;
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_build_dirs() {
+        let root = testdata("workspaces/build_dir");
+        let context = RequestContext::new_for_testing(Some(&root));
+        let text_document = TextDocumentIdentifier {
+            uri: Url::from_file_path(root.join("BUILD.gn")).unwrap(),
+        };
+
+        let result = build_dirs(
+            &context,
+            BuildDirsParams {
+                text_document: text_document.clone(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result,
+            BuildDirsResult {
+                build_dirs: vec![root.join("out/Debug"), root.join("out/Release")],
+                active_build_dir: None,
+            }
+        );
+
+        set_active_build_dir(
+            &context,
+            SetActiveBuildDirParams {
+                text_document: text_document.clone(),
+                build_dir: Some(PathBuf::from("out/Release")),
+            },
+        )
+        .await
+        .unwrap();
+        let result = build_dirs(
+            &context,
+            BuildDirsParams {
+                text_document: text_document.clone(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.active_build_dir, Some(root.join("out/Release")));
+
+        assert!(set_active_build_dir(
+            &context,
+            SetActiveBuildDirParams {
+                text_document,
+                build_dir: Some(PathBuf::from("out")),
+            },
+        )
+        .await
+        .is_err());
+    }
+}
@@ -0,0 +1,441 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{path::Path, sync::Arc, time::Instant};
+
+use tower_lsp::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams, Range,
+    SymbolKind, Url,
+};
+
+use crate::{
+    analyzer::{AnalyzedFile, AnalyzedStatement, TopLevelStatementsExt, WorkspaceAnalyzer},
+    common::{
+        builtins::{BUILTINS, TEMPLATE},
+        error::{Error, Result},
+        storage::Document,
+        utils::format_path,
+    },
+    parser::{string_content_span, Call, Node},
+    server::{
+        providers::{
+            references::{lookup_symbol_at, symbol_references, Symbol},
+            utils::get_text_document_path,
+        },
+        RequestContext,
+    },
+};
+
+/// Returns an item for a template definition or a target declaration, selecting
+/// the string holding its name.
+fn named_call_item(
+    document: &Document,
+    call: &Call,
+    kind: SymbolKind,
+    workspace_root: &Path,
+) -> Option<CallHierarchyItem> {
+    let name = call.only_arg()?.as_simple_string()?;
+    let string = call.only_arg()?.as_primary_string()?;
+    Some(CallHierarchyItem {
+        name: name.to_string(),
+        kind,
+        tags: None,
+        detail: Some(format_path(&document.path, workspace_root)),
+        uri: Url::from_file_path(&document.path).unwrap(),
+        range: document.line_index.range(call.span),
+        selection_range: document.line_index.range(string_content_span(string.span)),
+        data: None,
+    })
+}
+
+/// Returns an item for a builtin target function invoked by `call`. Builtins
+/// have no definition in the workspace, so the item points to the invocation.
+fn builtin_call_item(document: &Document, call: &Call) -> CallHierarchyItem {
+    CallHierarchyItem {
+        name: call.function.name.to_string(),
+        kind: SymbolKind::FUNCTION,
+        tags: None,
+        detail: Some("builtin".to_string()),
+        uri: Url::from_file_path(&document.path).unwrap(),
+        range: document.line_index.range(call.span),
+        selection_range: document.line_index.range(call.function.span),
+        data: None,
+    }
+}
+
+fn find_template_call<'a>(file: &'a AnalyzedFile, start: usize) -> Option<&'a Call<'a>> {
+    file.parsed_root
+        .get()
+        .calls()
+        .find(|call| call.function.name == TEMPLATE && call.span.start() == start)
+}
+
+fn template_item(
+    workspace: &WorkspaceAnalyzer,
+    symbol: &Symbol,
+    request_time: Instant,
+) -> Option<CallHierarchyItem> {
+    let Symbol::Template {
+        definition: (path, start),
+        ..
+    } = symbol
+    else {
+        return None;
+    };
+    let file = workspace.analyze_file(path, request_time);
+    let call = find_template_call(&file, *start)?;
+    named_call_item(
+        &file.document,
+        call,
+        SymbolKind::FUNCTION,
+        &file.workspace_root,
+    )
+}
+
+/// Resolves the template of a call hierarchy item previously returned to the
+/// client.
+fn lookup_item_template(
+    context: &RequestContext,
+    item: &CallHierarchyItem,
+) -> Result<Option<(Arc<WorkspaceAnalyzer>, Symbol)>> {
+    let path = item
+        .uri
+        .to_file_path()
+        .map_err(|_| Error::General(format!("Invalid file URI: {}", item.uri)))?;
+    let workspace = context.analyzer.workspace_for(&path)?;
+    let file = workspace.analyze_file(&path, context.request_time);
+    let Some(pos) = file.document.line_index.offset(item.selection_range.start) else {
+        return Ok(None);
+    };
+    let Some((symbol @ Symbol::Template { .. }, _)) =
+        lookup_symbol_at(&workspace, &file, pos, context.request_time)
+    else {
+        return Ok(None);
+    };
+    Ok(Some((workspace, symbol)))
+}
+
+pub async fn prepare_call_hierarchy(
+    context: &RequestContext,
+    params: CallHierarchyPrepareParams,
+) -> Result<Option<Vec<CallHierarchyItem>>> {
+    let path = get_text_document_path(&params.text_document_position_params.text_document)?;
+    let workspace = context.analyzer.workspace_for(&path)?;
+    let current_file = workspace.analyze_file(&path, context.request_time);
+
+    let Some(pos) = current_file
+        .document
+        .line_index
+        .offset(params.text_document_position_params.position)
+    else {
+        return Ok(None);
+    };
+
+    let Some((symbol, _)) = lookup_symbol_at(&workspace, &current_file, pos, context.request_time)
+    else {
+        return Ok(None);
+    };
+
+    Ok(template_item(&workspace, &symbol, context.request_time).map(|item| vec![item]))
+}
+
+pub async fn incoming_calls(
+    context: &RequestContext,
+    params: CallHierarchyIncomingCallsParams,
+) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+    // Require background indexing.
+    if !context.client.configurations().await.background_indexing {
+        return Ok(None);
+    }
+
+    let Some((workspace, symbol)) = lookup_item_template(context, &params.item)? else {
+        return Ok(None);
+    };
+    let definition_file = workspace.analyze_file(
+        &params.item.uri.to_file_path().unwrap(),
+        context.request_time,
+    );
+
+    let mut calls: Vec<CallHierarchyIncomingCall> = Vec::new();
+    for location in symbol_references(
+        &workspace,
+        &definition_file,
+        &symbol,
+        false,
+        context.request_time,
+    )
+    .await
+    {
+        let file =
+            workspace.analyze_file(&location.uri.to_file_path().unwrap(), context.request_time);
+        let Some(pos) = file.document.line_index.offset(location.range.start) else {
+            continue;
+        };
+        let root = file.parsed_root.get();
+
+        // Attribute the invocation to the innermost template containing it, or
+        // to the target it declares.
+        let from = root
+            .calls()
+            .filter(|call| {
+                call.function.name == TEMPLATE && call.span.start() <= pos && pos < call.span.end()
+            })
+            .last()
+            .map(|call| (call, SymbolKind::FUNCTION))
+            .or_else(|| {
+                root.calls()
+                    .find(|call| call.function.span.start() == pos)
+                    .map(|call| (call, SymbolKind::OBJECT))
+            })
+            .and_then(|(call, kind)| {
+                named_call_item(&file.document, call, kind, &file.workspace_root)
+            });
+        let Some(from) = from else {
+            continue;
+        };
+
+        if let Some(call) = calls.iter_mut().find(|call| {
+            call.from.uri == from.uri && call.from.selection_range == from.selection_range
+        }) {
+            call.from_ranges.push(location.range);
+        } else {
+            calls.push(CallHierarchyIncomingCall {
+                from,
+                from_ranges: vec![location.range],
+            });
+        }
+    }
+    calls.sort_by_key(|call| (call.from.uri.to_string(), call.from.range.start));
+
+    Ok(Some(calls))
+}
+
+pub async fn outgoing_calls(
+    context: &RequestContext,
+    params: CallHierarchyOutgoingCallsParams,
+) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+    let Some((workspace, symbol)) = lookup_item_template(context, &params.item)? else {
+        return Ok(None);
+    };
+    let Symbol::Template {
+        definition: (path, start),
+        ..
+    } = &symbol
+    else {
+        return Ok(None);
+    };
+
+    let file = workspace.analyze_file(path, context.request_time);
+    let Some(template) = file
+        .analyzed_root
+        .get()
+        .top_level_statements()
+        .find_map(|statement| match statement {
+            AnalyzedStatement::Template(template) if template.call.span.start() == *start => {
+                Some(template)
+            }
+            _ => None,
+        })
+    else {
+        return Ok(None);
+    };
+
+    let imports = workspace.analyze_imports(&file, context.request_time);
+    let line_index = &file.document.line_index;
+
+    let mut calls: Vec<(CallHierarchyItem, Vec<Range>)> = Vec::new();
+    for statement in template.body_block.top_level_statements() {
+        let AnalyzedStatement::Target(target) = statement else {
+            continue;
+        };
+        let call = target.call;
+        let pos = call.function.span.start();
+        let local_templates = file.local_templates_at(pos);
+        let to = if let Some(callee) = local_templates
+            .get(call.function.name)
+            .or_else(|| imports.get().templates.get(call.function.name))
+        {
+            named_call_item(
+                callee.document,
+                callee.call,
+                SymbolKind::FUNCTION,
+                &file.workspace_root,
+            )
+        } else if BUILTINS
+            .targets
+            .iter()
+            .any(|symbol| symbol.name == call.function.name)
+        {
+            Some(builtin_call_item(&file.document, call))
+        } else {
+            None
+        };
+        let Some(to) = to else {
+            continue;
+        };
+
+        let range = line_index.range(call.function.span);
+        if let Some((_, ranges)) = calls.iter_mut().find(|(item, _)| {
+            item.uri == to.uri && item.name == to.name && item.detail == to.detail
+        }) {
+            ranges.push(range);
+        } else {
+            calls.push((to, vec![range]));
+        }
+    }
+
+    Ok(Some(
+        calls
+            .into_iter()
+            .map(|(to, from_ranges)| CallHierarchyOutgoingCall { to, from_ranges })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{Position, TextDocumentIdentifier, TextDocumentPositionParams};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    fn uri(name: &str) -> Url {
+        Url::from_file_path(testdata(format!("workspaces/call_hierarchy/{name}"))).unwrap()
+    }
+
+    fn make_range(line: u32, start: u32, end: u32) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    fn new_context() -> RequestContext {
+        let context = RequestContext::new_for_testing(Some(&testdata("workspaces/call_hierarchy")));
+        // Background indexing is disabled in tests, so load files to be searched.
+        context
+            .analyzer
+            .analyze_file(
+                &uri("BUILD.gn").to_file_path().unwrap(),
+                context.request_time,
+            )
+            .unwrap();
+        context
+    }
+
+    async fn prepare_at(context: &RequestContext, line: u32, character: u32) -> CallHierarchyItem {
+        let items = prepare_call_hierarchy(
+            context,
+            CallHierarchyPrepareParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: uri("BUILD.gn"),
+                    },
+                    position: Position::new(line, character),
+                },
+                work_done_progress_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        items.into_iter().next().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_outgoing_calls() {
+        let context = new_context();
+        let item = prepare_at(&context, 16, 2).await;
+        assert_eq!(item.name, "widget");
+        assert_eq!(item.uri, uri("templates.gni"));
+        assert_eq!(item.selection_range, make_range(20, 10, 16));
+
+        let calls = outgoing_calls(
+            &context,
+            CallHierarchyOutgoingCallsParams {
+                item,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let calls: Vec<_> = calls
+            .into_iter()
+            .map(|call| (call.to.name, call.to.detail.unwrap(), call.from_ranges))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                (
+                    "base_widget".to_string(),
+                    "//templates.gni".to_string(),
+                    vec![make_range(21, 2, 13), make_range(25, 4, 15)]
+                ),
+                (
+                    "executable".to_string(),
+                    "builtin".to_string(),
+                    vec![make_range(28, 4, 14)]
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_incoming_calls() {
+        let context = new_context();
+        let item = prepare_at(&context, 19, 2).await;
+        assert_eq!(item.name, "base_widget");
+
+        let calls = incoming_calls(
+            &context,
+            CallHierarchyIncomingCallsParams {
+                item,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let calls: Vec<_> = calls
+            .into_iter()
+            .map(|call| {
+                (
+                    call.from.name,
+                    call.from.kind,
+                    call.from.uri,
+                    call.from_ranges,
+                )
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                (
+                    "b".to_string(),
+                    SymbolKind::OBJECT,
+                    uri("BUILD.gn"),
+                    vec![make_range(19, 0, 11)]
+                ),
+                (
+                    "widget".to_string(),
+                    SymbolKind::FUNCTION,
+                    uri("templates.gni"),
+                    vec![make_range(21, 2, 13), make_range(25, 4, 15)]
+                ),
+            ]
+        );
+    }
+}
@@ -24,7 +24,11 @@ use crate::{
     analyzer::WorkspaceAnalyzer,
     common::{error::Result, utils::format_path},
     server::{
-        providers::{references::target_references, utils::get_text_document_path},
+        providers::{
+            execute_command::{COPY_TARGET_LABEL, SHOW_TARGET_REFERENCES},
+            references::target_references,
+            utils::get_text_document_path,
+        },
         RequestContext,
     },
 };
@@ -58,7 +62,7 @@ async fn compute_references_lens(
     Ok(CodeLens {
         range,
         command: Some(Command {
-            command: "gn.showTargetReferences".to_string(),
+            command: SHOW_TARGET_REFERENCES.to_string(),
             title,
             arguments: Some(vec![
                 serde_json::to_value(range.start).unwrap(),
@@ -73,9 +77,6 @@ pub async fn code_lens(
     context: &RequestContext,
     params: CodeLensParams,
 ) -> Result<Option<Vec<CodeLens>>> {
-    if !context.options.vscode_extension {
-        return Ok(None);
-    }
     let configs = context.client.configurations().await;
     if !configs.target_lens {
         return Ok(None);
@@ -138,7 +139,7 @@ pub async fn code_lens(
             range,
             command: Some(Command {
                 title: "copy label".to_string(),
-                command: "gn.copyTargetLabel".to_string(),
+                command: COPY_TARGET_LABEL.to_string(),
                 arguments: Some(vec![Value::String(label)]),
             }),
             data: None,
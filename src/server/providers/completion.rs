@@ -12,7 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashSet, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::Path,
+    sync::Arc,
+};
 
 use either::Either;
 use itertools::Itertools;
@@ -24,7 +28,7 @@ use tower_lsp::lsp_types::{
 use crate::{
     analyzer::{AnalyzedFile, Template, Variable, WorkspaceAnalyzer},
     common::{builtins::BUILTINS, error::Result, utils::format_path},
-    parser::{Block, Node, Statement},
+    parser::{Block, Call, Node, Statement},
     server::{
         imports::create_import_edit, providers::utils::get_text_document_path, symbols::SymbolSet,
         RequestContext,
@@ -89,6 +93,16 @@ fn is_after_dot(data: &str, offset: usize) -> bool {
     false
 }
 
+/// Returns the name of the scope before the dot the cursor follows.
+fn scope_before_dot(data: &str, offset: usize) -> Option<&str> {
+    let is_identifier_char = |ch: char| ch.is_ascii_alphanumeric() || ch == '_';
+    let before_dot = data[..offset]
+        .trim_end_matches(is_identifier_char)
+        .strip_suffix('.')?;
+    let scope = &before_dot[before_dot.trim_end_matches(is_identifier_char).len()..];
+    (!scope.is_empty()).then_some(scope)
+}
+
 impl Variable<'_> {
     fn as_completion_item(&self, current_file: &AnalyzedFile, need_import: bool) -> CompletionItem {
         let first_assignment = self.assignments.first().unwrap();
@@ -206,6 +220,62 @@ fn compute_completion_context(parsed_root: &Block<'_>, offset: usize) -> Complet
     statement_context
 }
 
+/// Returns the innermost call whose block contains the position.
+fn enclosing_block_call<'i, 'n>(parsed_root: &'n Block<'i>, offset: usize) -> Option<&'n Call<'i>> {
+    parsed_root
+        .calls()
+        .filter(|call| {
+            call.block
+                .as_ref()
+                .is_some_and(|block| block.span.start() < offset && offset < block.span.end())
+        })
+        .last()
+}
+
+fn build_member_completions(
+    context: &RequestContext,
+    current_file: &Arc<AnalyzedFile>,
+    workspace: &WorkspaceAnalyzer,
+    offset: usize,
+    scope: &str,
+) -> Vec<CompletionItem> {
+    let members = workspace.analyze_scope_at(current_file, offset, scope, context.request_time);
+    let mut items: Vec<_> = members
+        .get()
+        .variables
+        .values()
+        .map(|member| CompletionItem {
+            kind: Some(CompletionItemKind::FIELD),
+            ..member.as_completion_item(current_file, false)
+        })
+        .collect();
+
+    // Members of scopes computed at runtime, e.g. by exec_script, are only
+    // known from their uses.
+    let used_members: BTreeSet<&str> = current_file
+        .parsed_root
+        .get()
+        .walk()
+        .filter_map(|node| node.as_scope_access())
+        .filter(|scope_access| {
+            scope_access.scope.name == scope
+                && !(scope_access.member.span.start() <= offset
+                    && offset <= scope_access.member.span.end())
+                && !members
+                    .get()
+                    .variables
+                    .contains_key(scope_access.member.name)
+        })
+        .map(|scope_access| scope_access.member.name)
+        .collect();
+    items.extend(used_members.into_iter().map(|name| CompletionItem {
+        label: name.to_string(),
+        kind: Some(CompletionItemKind::FIELD),
+        ..Default::default()
+    }));
+    items
+}
+
 async fn build_identifier_completions(
     context: &RequestContext,
     current_file: &Arc<AnalyzedFile>,
@@ -213,12 +283,6 @@ async fn build_identifier_completions(
     offset: usize,
     workspace_completion: bool,
 ) -> Result<Vec<CompletionItem>> {
-    // Handle identifier completions.
-    // If the cursor is after a dot, we can't make suggestions.
-    if is_after_dot(&current_file.document.data, offset) {
-        return Ok(Vec::new());
-    }
-
     let environment = workspace.analyze_at(current_file, offset, context.request_time);
     let symbols = SymbolSet::workspace(workspace).await;
 
@@ -330,6 +394,26 @@ async fn build_identifier_completions(
             ..Default::default()
         });
 
+    // Enumerate parameters of the invoked template.
+    let parameters = enclosing_block_call(current_file.parsed_root.get(), offset)
+        .and_then(|call| environment.get().templates.get(call.function.name))
+        .map(|template| template.parameters())
+        .unwrap_or_default();
+    let parameter_names: HashSet<&str> =
+        parameters.iter().map(|parameter| parameter.name).collect();
+    let parameter_items = parameters.iter().map(|parameter| CompletionItem {
+        label: parameter.name.to_string(),
+        kind: Some(CompletionItemKind::FIELD),
+        detail: Some(if parameter.required {
+            "Required template parameter".to_string()
+        } else {
+            "Template parameter".to_string()
+        }),
+        ..Default::default()
+    });
+    let target_variable_items =
+        target_variable_items.filter(|item| !parameter_names.contains(item.label.as_str()));
+
     // Keywords.
     let literal_items = ["true", "false"].map(|name| CompletionItem {
         label: name.to_string(),
@@ -357,8 +441,8 @@ async fn build_identifier_completions(
         }
         CompletionContext::Target => {
             // No external variables.
-            Ok(conditional_items
-                .into_iter()
+            Ok(parameter_items
+                .chain(conditional_items)
                 .chain(builtin_function_items)
                 .chain(builtin_target_items)
                 .chain(target_variable_items)
@@ -413,6 +497,17 @@ pub async fn completion(
         return Ok(None);
     }
 
+    // Handle scope member completions.
+    if is_after_dot(&current_file.document.data, offset) {
+        let items = match scope_before_dot(&current_file.document.data, offset) {
+            Some(scope) => {
+                build_member_completions(context, &current_file, &workspace, offset, scope)
+            }
+            None => Vec::new(),
+        };
+        return Ok(Some(CompletionResponse::Array(items)));
+    }
+
     // Handle identifier completions.
     let items = build_identifier_completions(
         context,
@@ -611,4 +706,74 @@ mod tests {
             assert_eq!(got, want, "{name}: got {got}, want {want}");
         }
     }
+
+    #[tokio::test]
+    async fn test_scope_members() {
+        let root = testdata("workspaces/scope");
+        let names: Vec<_> = run_completion(&root.join("BUILD.gn"), Position::new(30, 17))
+            .await
+            .sorted()
+            .collect();
+        assert_eq!(names, ["enable_foo", "extra", "level"]);
+
+        // Members of scopes computed by scripts are taken from their uses.
+        let names: Vec<_> = run_completion(&root.join("BUILD.gn"), Position::new(30, 65))
+            .await
+            .collect();
+        assert_eq!(names, ["version"]);
+    }
+
+    #[tokio::test]
+    async fn test_template_parameters() {
+        let names: Vec<_> = run_completion(
+            &testdata("workspaces/template_parameters/BUILD.gn"),
+            Position::new(17, 2),
+        )
+        .await
+        .collect();
+        assert_eq!(
+            names[..5],
+            [
+                "sources",
+                "deps",
+                "public_deps",
+                "extra_flags",
+                "output_name"
+            ]
+        );
+        assert!(!names.contains(&"variant_data".to_string()));
+        assert!(names.contains(&"cflags".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_wildcard_forward() {
+        let names: HashSet<_> = run_completion(
+            &testdata("workspaces/forward_variables/BUILD.gn"),
+            Position::new(18, 18),
+        )
+        .await
+        .collect();
+        assert!(names.contains("extra_deps"));
+        assert!(!names.contains("extra_data"));
+
+        // Excluded by a list defined in BUILDCONFIG.gn.
+        let names: HashSet<_> = run_completion(
+            &testdata("workspaces/forward_variables/BUILD.gn"),
+            Position::new(35, 8),
+        )
+        .await
+        .collect();
+        assert!(names.contains("INTERNAL_VARIABLES"));
+        assert!(!names.contains("internal_flag"));
+
+        // Excluded by a list assigned in the template.
+        let names: HashSet<_> = run_completion(
+            &testdata("workspaces/forward_variables/BUILD.gn"),
+            Position::new(44, 8),
+        )
+        .await
+        .collect();
+        assert!(names.contains("_excluded"));
+        assert!(!names.contains("local_flag"));
+    }
 }
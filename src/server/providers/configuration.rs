@@ -17,7 +17,7 @@ use tower_lsp::lsp_types::{DidChangeConfigurationParams, Url};
 
 use crate::server::RequestContext;
 
-use super::diagnostics::{publish_diagnostics, unpublish_diagnostics};
+use super::diagnostics::{publish_diagnostics, refresh_diagnostics, unpublish_diagnostics};
 
 pub async fn did_change_configuration(
     context: &RequestContext,
@@ -25,6 +25,10 @@ pub async fn did_change_configuration(
 ) {
     let config = context.client.configurations().await;
 
+    context
+        .analyzer
+        .set_build_dir_setting(config.active_build_dir.clone());
+
     let documents = context.storage.lock().unwrap().memory_docs();
 
     let mut tasks = Vec::new();
@@ -51,4 +55,6 @@ pub async fn did_change_configuration(
     }
 
     join_all(tasks).await;
+
+    refresh_diagnostics(context).await;
 }
@@ -0,0 +1,146 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use either::Either;
+use pest::Span;
+use tower_lsp::lsp_types::request::{GotoDeclarationParams, GotoDeclarationResponse};
+
+use crate::{
+    analyzer::AnalyzedFile,
+    common::{builtins::DECLARE_ARGS, error::Result},
+    parser::Node,
+    server::{
+        providers::{
+            goto_definition::{goto_definition, variable_assignment_link},
+            utils::{get_text_document_path, lookup_identifier_at},
+        },
+        RequestContext,
+    },
+};
+
+fn is_in_declare_args(file: &AnalyzedFile, span: Span) -> bool {
+    file.parsed_root
+        .get()
+        .walk()
+        .filter_map(|node| node.as_call())
+        .any(|call| {
+            call.function.name == DECLARE_ARGS
+                && call.span.start() <= span.start()
+                && span.end() <= call.span.end()
+        })
+}
+
+/// Finds the `declare_args` declarations of build arguments. Other symbols
+/// are not distinguished from their definitions.
+pub async fn goto_declaration(
+    context: &RequestContext,
+    params: GotoDeclarationParams,
+) -> Result<Option<GotoDeclarationResponse>> {
+    let path = get_text_document_path(&params.text_document_position_params.text_document)?;
+    let current_file = context.analyzer.analyze_file(&path, context.request_time)?;
+
+    let Some(pos) = current_file
+        .document
+        .line_index
+        .offset(params.text_document_position_params.position)
+    else {
+        return Ok(None);
+    };
+
+    if let Some(ident) = lookup_identifier_at(&current_file, pos) {
+        let environment = context
+            .analyzer
+            .analyze_at(&current_file, pos, context.request_time)?;
+        if let Some(variable) = environment
+            .get()
+            .variables
+            .get(ident.name)
+            .filter(|variable| variable.is_args)
+        {
+            let mut links = Vec::new();
+            for assignment in &variable.assignments {
+                // Arguments can't be declared by calls like forward_variables_from.
+                let Either::Left(statement) = assignment.assignment_or_call else {
+                    continue;
+                };
+                let file = context
+                    .analyzer
+                    .analyze_file(&assignment.document.path, context.request_time)?;
+                if is_in_declare_args(&file, statement.span) {
+                    links.push(variable_assignment_link(
+                        &current_file,
+                        ident.span,
+                        assignment,
+                    ));
+                }
+            }
+            if !links.is_empty() {
+                return Ok(Some(GotoDeclarationResponse::Link(links)));
+            }
+        }
+    }
+
+    goto_definition(context, params).await
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        GotoDefinitionParams, GotoDefinitionResponse, Position, TextDocumentIdentifier,
+        TextDocumentPositionParams, Url,
+    };
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    fn make_params(position: Position) -> GotoDefinitionParams {
+        GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(testdata("workspaces/declaration/BUILD.gn")).unwrap(),
+                },
+                position,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    fn target_lines(response: Option<GotoDefinitionResponse>) -> Vec<u32> {
+        let Some(GotoDefinitionResponse::Link(links)) = response else {
+            panic!("unexpected response: {response:?}");
+        };
+        links
+            .into_iter()
+            .map(|link| link.target_selection_range.start.line)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_goto_declaration() {
+        let context = RequestContext::new_for_testing(Some(&testdata("workspaces/declaration")));
+
+        let declarations = goto_declaration(&context, make_params(Position::new(14, 6)))
+            .await
+            .unwrap();
+        assert_eq!(target_lines(declarations), [16]);
+
+        // Definitions include overrides.
+        let definitions = goto_definition(&context, make_params(Position::new(14, 6)))
+            .await
+            .unwrap();
+        assert_eq!(target_lines(definitions), [16, 20]);
+    }
+}
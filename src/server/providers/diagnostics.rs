@@ -12,13 +12,71 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use tower_lsp::lsp_types::Url;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use itertools::Itertools;
+use tokio::sync::watch;
+use tower_lsp::lsp_types::{
+    Diagnostic, DocumentDiagnosticParams, DocumentDiagnosticReport, DocumentDiagnosticReportResult,
+    FullDocumentDiagnosticReport, PublishDiagnosticsParams, RelatedFullDocumentDiagnosticReport,
+    RelatedUnchangedDocumentDiagnosticReport, UnchangedDocumentDiagnosticReport, Url,
+    WorkspaceDiagnosticParams, WorkspaceDiagnosticReport, WorkspaceDiagnosticReportResult,
+    WorkspaceDocumentDiagnosticReport, WorkspaceFullDocumentDiagnosticReport,
+    WorkspaceUnchangedDocumentDiagnosticReport,
+};
 
 use crate::{
-    common::storage::DocumentVersion, diagnostics::compute_diagnostics, server::RequestContext,
+    analyzer::{AnalyzedFile, KnownValues, WorkspaceAnalyzer},
+    common::{
+        config::{Configurations, WorkspaceDiagnosticsScope},
+        error::Result,
+        storage::DocumentVersion,
+    },
+    diagnostics::compute_diagnostics,
+    server::{providers::utils::get_text_document_path, RequestContext},
 };
 
+/// Time to spend computing workspace diagnostics before pausing for as long,
+/// so that they take at most half of a core.
+const WORK_SLICE: Duration = Duration::from_millis(50);
+
+/// Interval to check workspace diagnostics again while a pull request is held,
+/// to pick up changes on disk the client does not notify.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Delay for consecutive changes, e.g. typing, to settle before checking
+/// workspace diagnostics again.
+const SETTLE_DELAY: Duration = Duration::from_millis(500);
+
+/// Returns whether the client requests diagnostics by itself, in which case
+/// they must not be pushed.
+fn client_pulls_diagnostics(context: &RequestContext) -> bool {
+    context
+        .client_capabilities
+        .text_document
+        .as_ref()
+        .is_some_and(|text_document| text_document.diagnostic.is_some())
+}
+
+fn document_revision(file: &AnalyzedFile) -> Option<i32> {
+    if let DocumentVersion::InMemory { revision } = file.document.version {
+        Some(revision)
+    } else {
+        None
+    }
+}
+
 pub async fn publish_diagnostics(context: &RequestContext, uri: &Url) {
+    if client_pulls_diagnostics(context) {
+        return;
+    }
+
     let Ok(path) = uri.to_file_path() else {
         return;
     };
@@ -32,23 +90,747 @@ pub async fn publish_diagnostics(context: &RequestContext, uri: &Url) {
         return;
     };
 
-    let diagnostics = compute_diagnostics(&current_file, &context.analyzer, context.request_time);
-
-    let version = if let DocumentVersion::InMemory { revision } = current_file.document.version {
-        Some(revision)
-    } else {
-        None
-    };
+    let diagnostics = compute_diagnostics(
+        &current_file,
+        &context.analyzer,
+        &config.assumed_args,
+        context.request_time,
+    );
 
     context
         .client
-        .publish_diagnostics(uri.clone(), diagnostics, version)
+        .publish_diagnostics(uri.clone(), diagnostics, document_revision(&current_file))
         .await;
 }
 
 pub async fn unpublish_diagnostics(context: &RequestContext, uri: &Url) {
+    if client_pulls_diagnostics(context) {
+        return;
+    }
+
     context
         .client
         .publish_diagnostics(uri.clone(), Vec::new(), None)
         .await;
 }
+
+/// Updates diagnostics of a document closed in the client. They are kept if the
+/// file is in the scope of workspace diagnostics.
+pub async fn close_diagnostics(context: &RequestContext, uri: &Url) {
+    let Ok(path) = uri.to_file_path() else {
+        return;
+    };
+
+    let config = context.client.configurations().await;
+    if config.background_indexing && is_in_scope(context, config.workspace_diagnostics, &path) {
+        publish_diagnostics(context, uri).await;
+    } else {
+        unpublish_diagnostics(context, uri).await;
+    }
+}
+
+/// Wakes up held workspace diagnostics requests after a change that may affect
+/// diagnostics of any file.
+pub fn notify_diagnostics_changed(context: &RequestContext) {
+    context.diagnostics_changes.send_replace(());
+}
+
+/// Asks the client to pull diagnostics again, e.g. after a file imported by
+/// open documents has changed.
+pub async fn refresh_diagnostics(context: &RequestContext) {
+    notify_diagnostics_changed(context);
+
+    let refresh_support = context
+        .client_capabilities
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.diagnostic.as_ref())
+        .and_then(|diagnostic| diagnostic.refresh_support)
+        .unwrap_or(false);
+    if refresh_support {
+        context.client.workspace_diagnostic_refresh().await.ok();
+    }
+}
+
+/// Returns an identifier of the diagnostics of a file, which changes whenever
+/// the file, its imports or the configuration affecting them change.
+fn diagnostics_result_id(
+    context: &RequestContext,
+    file: &Arc<AnalyzedFile>,
+    assumed_args: &KnownValues,
+) -> String {
+    let mut hasher = DefaultHasher::new();
+    context
+        .analyzer
+        .environment_key(file, context.request_time)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    for (name, value) in assumed_args.iter().sorted_by_key(|(name, _)| *name) {
+        name.hash(&mut hasher);
+        value.hash(&mut hasher);
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+fn full_report(items: Vec<Diagnostic>, result_id: Option<String>) -> FullDocumentDiagnosticReport {
+    FullDocumentDiagnosticReport { result_id, items }
+}
+
+pub async fn document_diagnostic(
+    context: &RequestContext,
+    params: DocumentDiagnosticParams,
+) -> Result<DocumentDiagnosticReportResult> {
+    let path = get_text_document_path(&params.text_document)?;
+
+    let config = context.client.configurations().await;
+    if !config.error_reporting {
+        return Ok(DocumentDiagnosticReportResult::Report(
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: full_report(Vec::new(), None),
+            }),
+        ));
+    }
+
+    let current_file = context.analyzer.analyze_file(&path, context.request_time)?;
+    let result_id = diagnostics_result_id(context, &current_file, &config.assumed_args);
+    if params.previous_result_id.as_ref() == Some(&result_id) {
+        return Ok(DocumentDiagnosticReportResult::Report(
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id,
+                },
+            }),
+        ));
+    }
+
+    let items = compute_diagnostics(
+        &current_file,
+        &context.analyzer,
+        &config.assumed_args,
+        context.request_time,
+    );
+    Ok(DocumentDiagnosticReportResult::Report(
+        DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+            related_documents: None,
+            full_document_diagnostic_report: full_report(items, Some(result_id)),
+        }),
+    ))
+}
+
+fn is_in_scope(context: &RequestContext, scope: WorkspaceDiagnosticsScope, path: &Path) -> bool {
+    match scope {
+        WorkspaceDiagnosticsScope::Off => false,
+        WorkspaceDiagnosticsScope::OpenFolders => context
+            .analyzer
+            .workspace_finder()
+            .client_roots()
+            .iter()
+            .any(|root| path.starts_with(root)),
+        WorkspaceDiagnosticsScope::Workspace => true,
+    }
+}
+
+/// Returns indexed files of a workspace in the scope of workspace
+/// diagnostics. Files in the directories of open documents come first, and
+/// the rest are grouped by directory.
+async fn scoped_workspace_files(
+    context: &RequestContext,
+    workspace: &WorkspaceAnalyzer,
+    scope: WorkspaceDiagnosticsScope,
+) -> Vec<Arc<AnalyzedFile>> {
+    if scope == WorkspaceDiagnosticsScope::Off {
+        return Vec::new();
+    }
+    let open_dirs: Vec<PathBuf> = context
+        .storage
+        .lock()
+        .unwrap()
+        .memory_docs()
+        .iter()
+        .filter_map(|document| document.path.parent().map(Path::to_path_buf))
+        .collect();
+
+    let mut files: Vec<_> = workspace
+        .scan_files()
+        .await
+        .into_iter()
+        .filter(|file| is_in_scope(context, scope, &file.document.path))
+        .collect();
+    files.sort_by_cached_key(|file| {
+        let path = &file.document.path;
+        let in_open_dir = path
+            .parent()
+            .is_some_and(|dir| open_dirs.iter().any(|open_dir| open_dir == dir));
+        (!in_open_dir, path.clone())
+    });
+    files
+}
+
+/// Returns indexed files of all workspaces in the scope of workspace
+/// diagnostics, or none if they are disabled.
+async fn scoped_files(context: &RequestContext, config: &Configurations) -> Vec<Arc<AnalyzedFile>> {
+    // Require background indexing.
+    if !config.error_reporting || !config.background_indexing {
+        return Vec::new();
+    }
+    let mut files = Vec::new();
+    for workspace in context.analyzer.workspaces().into_values() {
+        files.extend(
+            scoped_workspace_files(context, &workspace, config.workspace_diagnostics).await,
+        );
+    }
+    files
+}
+
+/// Returns reports of files in the scope of workspace diagnostics. Files the
+/// client has the latest results for are reported unchanged, and files that
+/// left the scope are reported without problems.
+async fn workspace_reports(
+    context: &RequestContext,
+    config: &Configurations,
+    previous_result_ids: &HashMap<Url, String>,
+) -> Vec<WorkspaceDocumentDiagnosticReport> {
+    let mut reports = Vec::new();
+    let mut reported_uris = HashSet::new();
+    let mut slice_start = Instant::now();
+    for file in scoped_files(context, config).await {
+        if slice_start.elapsed() >= WORK_SLICE {
+            tokio::time::sleep(WORK_SLICE).await;
+            slice_start = Instant::now();
+        }
+
+        let Ok(uri) = Url::from_file_path(&file.document.path) else {
+            continue;
+        };
+        reported_uris.insert(uri.clone());
+        let version = document_revision(&file).map(i64::from);
+        let result_id = diagnostics_result_id(context, &file, &config.assumed_args);
+        if previous_result_ids.get(&uri) == Some(&result_id) {
+            reports.push(WorkspaceDocumentDiagnosticReport::Unchanged(
+                WorkspaceUnchangedDocumentDiagnosticReport {
+                    uri,
+                    version,
+                    unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                        result_id,
+                    },
+                },
+            ));
+            continue;
+        }
+        let diagnostics = compute_diagnostics(
+            &file,
+            &context.analyzer,
+            &config.assumed_args,
+            context.request_time,
+        );
+        reports.push(WorkspaceDocumentDiagnosticReport::Full(
+            WorkspaceFullDocumentDiagnosticReport {
+                uri,
+                version,
+                full_document_diagnostic_report: full_report(diagnostics, Some(result_id)),
+            },
+        ));
+    }
+
+    for uri in previous_result_ids.keys() {
+        if !reported_uris.contains(uri) {
+            reports.push(WorkspaceDocumentDiagnosticReport::Full(
+                WorkspaceFullDocumentDiagnosticReport {
+                    uri: uri.clone(),
+                    version: None,
+                    full_document_diagnostic_report: full_report(Vec::new(), None),
+                },
+            ));
+        }
+    }
+    reports
+}
+
+/// Waits until diagnostics may have changed, or until it is time to check
+/// files on disk again.
+async fn wait_for_changes(changes: &mut watch::Receiver<()>) {
+    if let Ok(Ok(())) = tokio::time::timeout(RECHECK_INTERVAL, changes.changed()).await {
+        while let Ok(Ok(())) = tokio::time::timeout(SETTLE_DELAY, changes.changed()).await {}
+    }
+}
+
+/// Reports diagnostics of files in the scope of workspace diagnostics. As
+/// clients pull again as soon as a request returns, the request is held until
+/// the diagnostics of some file have changed since the previous pull. It
+/// returns right away if there is nothing to report, e.g. if the scope is off.
+pub async fn workspace_diagnostic(
+    context: &RequestContext,
+    params: WorkspaceDiagnosticParams,
+) -> Result<WorkspaceDiagnosticReportResult> {
+    let previous_result_ids: HashMap<Url, String> = params
+        .previous_result_ids
+        .into_iter()
+        .map(|previous| (previous.uri, previous.value))
+        .collect();
+
+    let mut context = context.clone();
+    loop {
+        let mut changes = context.diagnostics_changes.subscribe();
+        let config = context.client.configurations().await;
+        let items = workspace_reports(&context, &config, &previous_result_ids).await;
+        if items.is_empty()
+            || items
+                .iter()
+                .any(|item| matches!(item, WorkspaceDocumentDiagnosticReport::Full(_)))
+        {
+            return Ok(WorkspaceDiagnosticReportResult::Report(
+                WorkspaceDiagnosticReport { items },
+            ));
+        }
+
+        wait_for_changes(&mut changes).await;
+        context.request_time = Instant::now();
+    }
+}
+
+/// Diagnostics published for files in the scope of workspace diagnostics.
+#[derive(Default)]
+struct PublishedDiagnostics {
+    result_ids: HashMap<Url, String>,
+    /// Files the client may have problems of.
+    dirty_uris: HashSet<Url>,
+}
+
+impl PublishedDiagnostics {
+    /// Records new workspace reports and returns the diagnostics to publish.
+    /// Clean files are published only to clear problems published before.
+    /// Open documents are published by `publish_diagnostics` instead.
+    fn update(
+        &mut self,
+        reports: Vec<WorkspaceDocumentDiagnosticReport>,
+        open_paths: &HashSet<PathBuf>,
+    ) -> Vec<PublishDiagnosticsParams> {
+        let mut params = Vec::new();
+        for report in reports {
+            let WorkspaceDocumentDiagnosticReport::Full(report) = report else {
+                continue;
+            };
+            let uri = report.uri;
+            let FullDocumentDiagnosticReport { result_id, items } =
+                report.full_document_diagnostic_report;
+            match result_id {
+                Some(result_id) => self.result_ids.insert(uri.clone(), result_id),
+                None => self.result_ids.remove(&uri),
+            };
+
+            if uri
+                .to_file_path()
+                .is_ok_and(|path| open_paths.contains(&path))
+            {
+                self.dirty_uris.insert(uri);
+                continue;
+            }
+            let was_dirty = self.dirty_uris.remove(&uri);
+            if items.is_empty() && !was_dirty {
+                continue;
+            }
+            if !items.is_empty() {
+                self.dirty_uris.insert(uri.clone());
+            }
+            params.push(PublishDiagnosticsParams {
+                uri,
+                diagnostics: items,
+                version: report
+                    .version
+                    .and_then(|version| i32::try_from(version).ok()),
+            });
+        }
+        params
+    }
+}
+
+/// Spawns a task publishing diagnostics of files in the scope of workspace
+/// diagnostics, for clients that do not pull them. Files are published again
+/// whenever their results change, e.g. after an imported file is edited or
+/// the scope is changed.
+pub fn spawn_workspace_diagnostics_publisher(mut context: RequestContext) {
+    if client_pulls_diagnostics(&context) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut published = PublishedDiagnostics::default();
+        loop {
+            let mut changes = context.diagnostics_changes.subscribe();
+            context.request_time = Instant::now();
+            let config = context.client.configurations().await;
+            let open_paths: HashSet<PathBuf> = context
+                .storage
+                .lock()
+                .unwrap()
+                .memory_docs()
+                .iter()
+                .map(|document| document.path.clone())
+                .collect();
+
+            let reports = workspace_reports(&context, &config, &published.result_ids).await;
+            for params in published.update(reports, &open_paths) {
+                context
+                    .client
+                    .publish_diagnostics(params.uri, params.diagnostics, params.version)
+                    .await;
+            }
+
+            wait_for_changes(&mut changes).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{DiagnosticTag, Position, TextDocumentIdentifier};
+
+    use crate::{analyzer::Value, common::testutils::testdata};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_document_diagnostic() {
+        let report = document_diagnostic(
+            &RequestContext::new_for_testing(Some(&testdata("workspaces/diagnostics"))),
+            DocumentDiagnosticParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(testdata("workspaces/diagnostics/BUILD.gn")).unwrap(),
+                },
+                identifier: None,
+                previous_result_id: None,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(report)) = report
+        else {
+            panic!("unexpected report: {report:?}");
+        };
+        let messages: Vec<_> = report
+            .full_document_diagnostic_report
+            .items
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+        assert_eq!(messages, ["missing_deps not defined"]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_parameters() {
+        let root = testdata("workspaces/template_parameters");
+        let report = document_diagnostic(
+            &RequestContext::new_for_testing(Some(&root)),
+            DocumentDiagnosticParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(root.join("BUILD.gn")).unwrap(),
+                },
+                identifier: None,
+                previous_result_id: None,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(report)) = report
+        else {
+            panic!("unexpected report: {report:?}");
+        };
+        let diagnostics: Vec<_> = report
+            .full_document_diagnostic_report
+            .items
+            .into_iter()
+            .map(|diagnostic| (diagnostic.range.start, diagnostic.message))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [(
+                Position::new(16, 0),
+                "Missing required parameter sources of my_library: my_library needs sources"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wildcard_forward() {
+        let root = testdata("workspaces/forward_variables");
+        let report = document_diagnostic(
+            &RequestContext::new_for_testing(Some(&root)),
+            DocumentDiagnosticParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(root.join("BUILD.gn")).unwrap(),
+                },
+                identifier: None,
+                previous_result_id: None,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(report)) = report
+        else {
+            panic!("unexpected report: {report:?}");
+        };
+        let diagnostics: Vec<_> = report
+            .full_document_diagnostic_report
+            .items
+            .into_iter()
+            .map(|diagnostic| (diagnostic.range.start, diagnostic.message))
+            .collect();
+        // Excluded variables are not forwarded, including those excluded by
+        // a list defined in BUILDCONFIG.gn.
+        assert_eq!(
+            diagnostics,
+            [
+                (Position::new(27, 8), "my_flag not defined".to_string()),
+                (
+                    Position::new(35, 8),
+                    "internal_flag not defined".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_document_diagnostic_unchanged() {
+        let root = testdata("workspaces/diagnostics");
+        let context = RequestContext::new_for_testing(Some(&root));
+        let pull = |previous_result_id: Option<String>| {
+            document_diagnostic(
+                &context,
+                DocumentDiagnosticParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(root.join("BUILD.gn")).unwrap(),
+                    },
+                    identifier: None,
+                    previous_result_id,
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                },
+            )
+        };
+
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(report)) =
+            pull(None).await.unwrap()
+        else {
+            panic!("expected a full report");
+        };
+        let result_id = report.full_document_diagnostic_report.result_id.unwrap();
+
+        let report = pull(Some(result_id.clone())).await.unwrap();
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Unchanged(report)) =
+            report
+        else {
+            panic!("unexpected report: {report:?}");
+        };
+        assert_eq!(
+            report.unchanged_document_diagnostic_report.result_id,
+            result_id
+        );
+
+        // Editing an imported file changes the result.
+        context.storage.lock().unwrap().load_to_memory(
+            &root.join("common.gni"),
+            "missing_deps = []\n",
+            1,
+        );
+        context
+            .analyzer
+            .file_changed(&root.join("common.gni"), context.request_time);
+        let report = pull(Some(result_id)).await.unwrap();
+        assert!(matches!(
+            report,
+            DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_workspace_reports() {
+        let root = testdata("workspaces/diagnostics");
+        let context = RequestContext::new_for_testing(Some(&root));
+        for name in ["BUILD.gn", "BUILDCONFIG.gn", "common.gni"] {
+            context
+                .analyzer
+                .analyze_file(&root.join(name), context.request_time)
+                .unwrap();
+        }
+        let build_uri = Url::from_file_path(root.join("BUILD.gn")).unwrap();
+        let common_uri = Url::from_file_path(root.join("common.gni")).unwrap();
+
+        let mut config = Configurations::default();
+        assert!(workspace_reports(&context, &config, &HashMap::new())
+            .await
+            .is_empty());
+
+        config.workspace_diagnostics = WorkspaceDiagnosticsScope::Workspace;
+        let reports = workspace_reports(&context, &config, &HashMap::new()).await;
+        let full_reports: HashMap<_, _> = reports
+            .into_iter()
+            .map(|report| match report {
+                WorkspaceDocumentDiagnosticReport::Full(full) => {
+                    (full.uri, full.full_document_diagnostic_report)
+                }
+                _ => panic!("unexpected report: {report:?}"),
+            })
+            .collect();
+        assert_eq!(full_reports[&build_uri].items.len(), 1);
+        assert_eq!(full_reports[&common_uri].items.len(), 0);
+
+        // Results the client already has are reported unchanged.
+        let previous_result_ids: HashMap<_, _> = full_reports
+            .into_iter()
+            .map(|(uri, report)| (uri, report.result_id.unwrap()))
+            .collect();
+        let reports = workspace_reports(&context, &config, &previous_result_ids).await;
+        assert_eq!(reports.len(), previous_result_ids.len());
+        assert!(reports
+            .iter()
+            .all(|report| matches!(report, WorkspaceDocumentDiagnosticReport::Unchanged(_))));
+
+        // Files out of the scope are cleared.
+        config.workspace_diagnostics = WorkspaceDiagnosticsScope::Off;
+        let reports = workspace_reports(&context, &config, &previous_result_ids).await;
+        assert_eq!(reports.len(), previous_result_ids.len());
+        assert!(reports.iter().all(|report| matches!(
+            report,
+            WorkspaceDocumentDiagnosticReport::Full(full)
+                if full.full_document_diagnostic_report.items.is_empty()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_workspace_diagnostic_off() {
+        let root = testdata("workspaces/diagnostics");
+        let context = RequestContext::new_for_testing(Some(&root));
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            workspace_diagnostic(
+                &context,
+                WorkspaceDiagnosticParams {
+                    identifier: None,
+                    previous_result_ids: Vec::new(),
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                },
+            ),
+        )
+        .await
+        .expect("request is held")
+        .unwrap();
+        let WorkspaceDiagnosticReportResult::Report(report) = result else {
+            panic!("unexpected result: {result:?}");
+        };
+        assert!(report.items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_published_diagnostics() {
+        let root = testdata("workspaces/diagnostics");
+        let context = RequestContext::new_for_testing(Some(&root));
+        for name in ["BUILD.gn", "BUILDCONFIG.gn", "common.gni"] {
+            context
+                .analyzer
+                .analyze_file(&root.join(name), context.request_time)
+                .unwrap();
+        }
+        let build_uri = Url::from_file_path(root.join("BUILD.gn")).unwrap();
+
+        let mut config = Configurations {
+            workspace_diagnostics: WorkspaceDiagnosticsScope::Workspace,
+            ..Default::default()
+        };
+        let mut published = PublishedDiagnostics::default();
+        let mut update = async |config: &Configurations, open_paths: HashSet<PathBuf>| {
+            let reports = workspace_reports(&context, config, &published.result_ids).await;
+            published
+                .update(reports, &open_paths)
+                .into_iter()
+                .map(|params| (params.uri, params.diagnostics.len()))
+                .collect::<Vec<_>>()
+        };
+
+        // Only files with problems are published.
+        assert_eq!(
+            update(&config, HashSet::new()).await,
+            [(build_uri.clone(), 1)]
+        );
+        // Nothing is published again until results change.
+        assert_eq!(update(&config, HashSet::new()).await, []);
+
+        // Files leaving the scope are cleared.
+        config.workspace_diagnostics = WorkspaceDiagnosticsScope::Off;
+        assert_eq!(update(&config, HashSet::new()).await, [(build_uri, 0)]);
+        assert_eq!(update(&config, HashSet::new()).await, []);
+    }
+
+    #[test]
+    fn test_inactive_branches() {
+        let root = testdata("workspaces/inactive");
+        let context = RequestContext::new_for_testing(Some(&root));
+        context
+            .analyzer
+            .workspace_for(&root)
+            .unwrap()
+            .set_active_build_dir(Some(root.join("out/Release")));
+        let path = root.join("BUILD.gn");
+
+        let inactive_starts = |assumed_args: &[(&str, Value)]| -> Vec<Position> {
+            let file = context
+                .analyzer
+                .analyze_file(&path, context.request_time)
+                .unwrap();
+            let assumed_args: KnownValues = assumed_args
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect();
+            compute_diagnostics(
+                &file,
+                &context.analyzer,
+                &assumed_args,
+                context.request_time,
+            )
+            .into_iter()
+            .filter(|diagnostic| diagnostic.tags == Some(vec![DiagnosticTag::UNNECESSARY]))
+            .map(|diagnostic| diagnostic.range.start)
+            .collect()
+        };
+
+        // Files not open in the editor are not reported.
+        assert!(inactive_starts(&[]).is_empty());
+
+        let data = std::fs::read_to_string(&path).unwrap();
+        context
+            .storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&path, &data, 1);
+        context.analyzer.file_changed(&path, context.request_time);
+
+        // args.gn sets is_debug = false.
+        assert_eq!(inactive_starts(&[]), [Position::new(14, 14)]);
+        assert_eq!(
+            inactive_starts(&[("is_win", Value::Bool(true))]),
+            [Position::new(14, 14), Position::new(20, 7)]
+        );
+        // use_asan is derived from is_debug in args.gn.
+        assert_eq!(
+            inactive_starts(&[("current_cpu", Value::String("arm64".to_string()))]),
+            [Position::new(14, 14), Position::new(22, 42)]
+        );
+        // Assumed args take precedence over args.gn.
+        assert_eq!(
+            inactive_starts(&[("is_debug", Value::Bool(true))]),
+            [Position::new(17, 7)]
+        );
+    }
+}
@@ -13,12 +13,15 @@
 // limitations under the License.
 
 use tower_lsp::lsp_types::{
-    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DidChangeTextDocumentParams, DidChangeWatchedFilesParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams,
 };
 
 use crate::server::RequestContext;
 
-use super::diagnostics::{publish_diagnostics, unpublish_diagnostics};
+use super::diagnostics::{
+    close_diagnostics, notify_diagnostics_changed, publish_diagnostics, refresh_diagnostics,
+};
 
 pub async fn did_open(context: &RequestContext, params: DidOpenTextDocumentParams) {
     let Ok(path) = params.text_document.uri.to_file_path() else {
@@ -32,23 +35,36 @@ pub async fn did_open(context: &RequestContext, params: DidOpenTextDocumentParam
     );
 
     publish_diagnostics(context, &params.text_document.uri).await;
+    notify_diagnostics_changed(context);
 }
 
 pub async fn did_change(context: &RequestContext, params: DidChangeTextDocumentParams) {
     let Ok(path) = params.text_document.uri.to_file_path() else {
         return;
     };
-    let Some(change) = params.content_changes.first() else {
-        return;
-    };
 
-    context.storage.lock().unwrap().load_to_memory(
+    context.storage.lock().unwrap().apply_changes(
         &path,
-        &change.text,
+        &params.content_changes,
         params.text_document.version,
     );
 
     publish_diagnostics(context, &params.text_document.uri).await;
+    notify_diagnostics_changed(context);
+}
+
+pub async fn did_change_watched_files(
+    context: &RequestContext,
+    params: DidChangeWatchedFilesParams,
+) {
+    for change in params.changes {
+        let Ok(path) = change.uri.to_file_path() else {
+            continue;
+        };
+        context.analyzer.file_changed(&path, context.request_time);
+    }
+
+    refresh_diagnostics(context).await;
 }
 
 pub async fn did_close(context: &RequestContext, params: DidCloseTextDocumentParams) {
@@ -56,7 +72,8 @@ pub async fn did_close(context: &RequestContext, params: DidCloseTextDocumentPar
         return;
     };
 
-    unpublish_diagnostics(context, &params.text_document.uri).await;
-
     context.storage.lock().unwrap().unload_from_memory(&path);
+
+    close_diagnostics(context, &params.text_document.uri).await;
+    notify_diagnostics_changed(context);
 }
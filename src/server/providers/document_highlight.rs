@@ -0,0 +1,133 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind, DocumentHighlightParams};
+
+use crate::{
+    common::error::Result,
+    server::{
+        providers::{
+            references::{lookup_symbol_at, symbol_occurrences},
+            utils::get_text_document_path,
+        },
+        RequestContext,
+    },
+};
+
+pub async fn document_highlight(
+    context: &RequestContext,
+    params: DocumentHighlightParams,
+) -> Result<Option<Vec<DocumentHighlight>>> {
+    let path = get_text_document_path(&params.text_document_position_params.text_document)?;
+    let workspace = context.analyzer.workspace_for(&path)?;
+    let current_file = workspace.analyze_file(&path, context.request_time);
+
+    let Some(pos) = current_file
+        .document
+        .line_index
+        .offset(params.text_document_position_params.position)
+    else {
+        return Ok(None);
+    };
+
+    let Some((symbol, _)) = lookup_symbol_at(&workspace, &current_file, pos, context.request_time)
+    else {
+        return Ok(None);
+    };
+
+    let highlights = symbol_occurrences(&workspace, &current_file, &symbol, context.request_time)
+        .into_iter()
+        .map(|occurrence| DocumentHighlight {
+            range: current_file.document.line_index.range(occurrence.span),
+            kind: Some(if occurrence.is_write {
+                DocumentHighlightKind::WRITE
+            } else {
+                DocumentHighlightKind::READ
+            }),
+        })
+        .collect();
+
+    Ok(Some(highlights))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        Position, Range, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+    };
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    async fn highlights_at(line: u32, character: u32) -> Vec<(Range, DocumentHighlightKind)> {
+        let highlights = document_highlight(
+            &RequestContext::new_for_testing(Some(&testdata("workspaces/document_highlight"))),
+            DocumentHighlightParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(testdata(
+                            "workspaces/document_highlight/BUILD.gn",
+                        ))
+                        .unwrap(),
+                    },
+                    position: Position::new(line, character),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let mut highlights: Vec<_> = highlights
+            .into_iter()
+            .map(|highlight| (highlight.range, highlight.kind.unwrap()))
+            .collect();
+        highlights.sort_by_key(|(range, _)| range.start);
+        highlights
+    }
+
+    fn make_range(line: u32, start: u32, end: u32) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[tokio::test]
+    async fn test_document_highlight() {
+        assert_eq!(
+            highlights_at(18, 15).await,
+            [
+                (make_range(14, 0, 14), DocumentHighlightKind::WRITE),
+                (make_range(15, 0, 14), DocumentHighlightKind::WRITE),
+                (make_range(18, 12, 26), DocumentHighlightKind::READ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_document_highlight_scoped() {
+        assert_eq!(
+            highlights_at(19, 3).await,
+            [
+                (make_range(18, 2, 9), DocumentHighlightKind::WRITE),
+                (make_range(19, 2, 9), DocumentHighlightKind::WRITE),
+            ]
+        );
+        assert_eq!(
+            highlights_at(23, 3).await,
+            [(make_range(23, 2, 9), DocumentHighlightKind::WRITE)]
+        );
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tower_lsp::lsp_types::{ExecuteCommandParams, Location, MessageType, ShowDocumentParams};
+
+use crate::{
+    common::error::{Error, Result},
+    server::RequestContext,
+};
+
+pub const SHOW_TARGET_REFERENCES: &str = "gn.showTargetReferences";
+pub const COPY_TARGET_LABEL: &str = "gn.copyTargetLabel";
+
+/// Commands executed by the server for clients without the VSCode extension,
+/// which implements them on the client side.
+pub fn server_commands() -> Vec<String> {
+    [SHOW_TARGET_REFERENCES, COPY_TARGET_LABEL]
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+fn get_argument<T: DeserializeOwned>(arguments: &[Value], index: usize) -> Result<T> {
+    let value = arguments
+        .get(index)
+        .ok_or_else(|| Error::General(format!("Missing argument #{index}")))?;
+    Ok(serde_json::from_value(value.clone())?)
+}
+
+fn format_location(location: &Location) -> String {
+    let path = location
+        .uri
+        .to_file_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| location.uri.to_string());
+    format!(
+        "{}:{}:{}",
+        path,
+        location.range.start.line + 1,
+        location.range.start.character + 1
+    )
+}
+
+/// Opens the only reference, or lists references in a message. Locations are
+/// also returned for clients that can present them.
+async fn show_target_references(context: &RequestContext, locations: &[Location]) {
+    let supports_show_document = context
+        .client_capabilities
+        .window
+        .as_ref()
+        .and_then(|window| window.show_document.as_ref())
+        .is_some_and(|show_document| show_document.support);
+
+    if let [location] = locations {
+        if supports_show_document {
+            let params = ShowDocumentParams {
+                uri: location.uri.clone(),
+                external: None,
+                take_focus: Some(true),
+                selection: Some(location.range),
+            };
+            if let Ok(true) = context.client.show_document(params).await {
+                return;
+            }
+        }
+    }
+
+    let message = match locations.len() {
+        0 => "No references".to_string(),
+        1 => "1 reference:".to_string(),
+        n => format!("{n} references:"),
+    };
+    let message = std::iter::once(message)
+        .chain(locations.iter().map(format_location))
+        .collect::<Vec<_>>()
+        .join("\n");
+    context
+        .client
+        .show_message(MessageType::INFO, message)
+        .await;
+}
+
+pub async fn execute_command(
+    context: &RequestContext,
+    params: ExecuteCommandParams,
+) -> Result<Option<Value>> {
+    match params.command.as_str() {
+        SHOW_TARGET_REFERENCES => {
+            let locations: Vec<Location> = get_argument(&params.arguments, 1)?;
+            show_target_references(context, &locations).await;
+            Ok(Some(serde_json::to_value(locations)?))
+        }
+        COPY_TARGET_LABEL => {
+            // The server has no access to the clipboard, so show the label
+            // to let users copy it.
+            let label: String = get_argument(&params.arguments, 0)?;
+            context.client.show_message(MessageType::INFO, &label).await;
+            Ok(Some(Value::String(label)))
+        }
+        command => Err(Error::General(format!("Unknown command: {command}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tower_lsp::lsp_types::{Position, Range, Url};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    fn make_params(command: &str, arguments: Vec<Value>) -> ExecuteCommandParams {
+        ExecuteCommandParams {
+            command: command.to_string(),
+            arguments,
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_command() {
+        let context = RequestContext::new_for_testing(Some(&testdata("workspaces/code_lens")));
+
+        let location = Location::new(
+            Url::from_file_path(testdata("workspaces/code_lens/BUILD.gn")).unwrap(),
+            Range::new(Position::new(19, 11), Position::new(19, 17)),
+        );
+        let result = execute_command(
+            &context,
+            make_params(
+                SHOW_TARGET_REFERENCES,
+                vec![json!(Position::new(16, 0)), json!([location.clone()])],
+            ),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, Some(json!([location])));
+
+        let result = execute_command(
+            &context,
+            make_params(COPY_TARGET_LABEL, vec![json!("//:lib")]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, Some(json!("//:lib")));
+
+        assert!(
+            execute_command(&context, make_params("gn.unknown", Vec::new()))
+                .await
+                .is_err()
+        );
+        assert!(
+            execute_command(&context, make_params(COPY_TARGET_LABEL, Vec::new()))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pest::Span;
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind, FoldingRangeParams};
+
+use crate::{
+    common::{error::Result, utils::OwnedLineIndex},
+    parser::{Block, Node},
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+/// Returns a range folding the lines between the brackets, keeping the closing
+/// bracket visible.
+fn bracket_folding_range(span: Span, line_index: &OwnedLineIndex) -> Option<FoldingRange> {
+    let range = line_index.range(span);
+    if range.end.line <= range.start.line + 1 {
+        return None;
+    }
+    Some(FoldingRange {
+        start_line: range.start.line,
+        end_line: range.end.line - 1,
+        kind: Some(FoldingRangeKind::Region),
+        ..Default::default()
+    })
+}
+
+/// Returns ranges for blocks and multi-line lists. Blocks of if/else chains
+/// are folded individually, so that the else clauses remain visible.
+fn node_folding_ranges(root: &Block, line_index: &OwnedLineIndex) -> Vec<FoldingRange> {
+    root.walk()
+        .skip(1)
+        .filter_map(|node| {
+            if let Some(block) = node.as_block() {
+                bracket_folding_range(block.span, line_index)
+            } else if let Some(list) = node.as_list() {
+                bracket_folding_range(list.span, line_index)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns ranges for runs of consecutive comment lines.
+fn comment_folding_ranges(data: &str) -> Vec<FoldingRange> {
+    let mut ranges = Vec::new();
+    let mut run_start: Option<u32> = None;
+    for (line, text) in data.lines().chain([""]).enumerate() {
+        let line = line as u32;
+        if text.trim_start().starts_with('#') {
+            run_start.get_or_insert(line);
+            continue;
+        }
+        if let Some(start_line) = run_start.take() {
+            if line - 1 > start_line {
+                ranges.push(FoldingRange {
+                    start_line,
+                    end_line: line - 1,
+                    kind: Some(FoldingRangeKind::Comment),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    ranges
+}
+
+pub async fn folding_range(
+    context: &RequestContext,
+    params: FoldingRangeParams,
+) -> Result<Option<Vec<FoldingRange>>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let current_file = context.analyzer.analyze_file(&path, context.request_time)?;
+
+    let mut ranges = node_folding_ranges(
+        current_file.parsed_root.get(),
+        &current_file.document.line_index,
+    );
+    ranges.extend(comment_folding_ranges(&current_file.document.data));
+    ranges.sort_by_key(|range| (range.start_line, range.end_line));
+
+    Ok(Some(ranges))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{TextDocumentIdentifier, Url};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_folding_range() {
+        let ranges = folding_range(
+            &RequestContext::new_for_testing(Some(&testdata("workspaces/folding_range"))),
+            FoldingRangeParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(testdata("workspaces/folding_range/BUILD.gn"))
+                        .unwrap(),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let ranges: Vec<_> = ranges
+            .into_iter()
+            .map(|range| (range.start_line, range.end_line, range.kind.unwrap()))
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                (0, 12, FoldingRangeKind::Comment),
+                (14, 15, FoldingRangeKind::Comment),
+                (16, 20, FoldingRangeKind::Region),
+                (17, 19, FoldingRangeKind::Region),
+                (21, 22, FoldingRangeKind::Region),
+                (23, 26, FoldingRangeKind::Region),
+                (24, 25, FoldingRangeKind::Region),
+            ]
+        );
+    }
+}
@@ -12,23 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::process::Stdio;
+use std::{collections::HashSet, process::Stdio};
 
 use pest::Span;
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     process::Command,
 };
-use tower_lsp::lsp_types::{DocumentFormattingParams, TextEdit};
+use tower_lsp::lsp_types::{
+    DocumentFormattingParams, DocumentOnTypeFormattingParams, DocumentRangeFormattingParams,
+    Position, Range, TextEdit,
+};
 
 use crate::{
+    analyzer::AnalyzedFile,
     common::{
         binary::find_gn_binary,
         error::{Error, Result},
     },
+    parser::{Expr, Node},
     server::{providers::utils::get_text_document_path, RequestContext},
 };
 
+const INDENT_WIDTH: usize = 2;
+
 pub async fn formatting(
     context: &RequestContext,
     params: DocumentFormattingParams,
@@ -99,3 +106,253 @@ pub async fn formatting(
         new_text: formatted,
     }]))
 }
+
+/// Re-indents lines with the parsed syntax tree, without the gn binary.
+struct Indenter<'i> {
+    data: &'i str,
+    /// Spans of blocks and lists, including their brackets.
+    brackets: Vec<Span<'i>>,
+    /// Offsets where statements and list items start.
+    anchors: HashSet<usize>,
+}
+
+impl<'i> Indenter<'i> {
+    fn new(file: &'i AnalyzedFile) -> Self {
+        let root = file.parsed_root.get();
+        let mut brackets = Vec::new();
+        let mut anchors = HashSet::new();
+        for node in root.walk().skip(1) {
+            if let Some(block) = node.as_block() {
+                brackets.push(block.span);
+            } else if let Some(list) = node.as_list() {
+                brackets.push(list.span);
+                anchors.extend(list.values.iter().map(|value: &Expr| value.span().start()));
+            } else if let Some(statement) = node.as_statement() {
+                anchors.insert(statement.span().start());
+            }
+        }
+        Indenter {
+            data: &file.document.data,
+            brackets,
+            anchors,
+        }
+    }
+
+    /// Returns the indentation of a line starting with a token at the offset,
+    /// if the line is not a continuation of an expression.
+    fn indent_at(&self, pos: usize) -> Option<usize> {
+        let rest = &self.data[pos..];
+        let is_closing = self.brackets.iter().any(|span| span.end() == pos + 1);
+        if !(self.anchors.contains(&pos) || is_closing || rest.starts_with('#')) {
+            return None;
+        }
+        let depth = self
+            .brackets
+            .iter()
+            .filter(|span| span.start() < pos && pos + 1 < span.end())
+            .count();
+        Some(depth * INDENT_WIDTH)
+    }
+
+    /// Computes edits re-indenting the lines from `first_line` to `last_line`.
+    /// Preceding lines are only read to shift continuation lines. A blank line
+    /// may be indented as well, e.g. after a newline is typed.
+    fn reindent(&self, first_line: u32, last_line: u32, blank_line: Option<u32>) -> Vec<TextEdit> {
+        let mut edits = Vec::new();
+        // Continuation lines are shifted as much as the last indented line.
+        let mut shift: isize = 0;
+        let mut offset = 0;
+        for (line, text) in self.data.split_inclusive('\n').enumerate() {
+            let line = line as u32;
+            if line > last_line {
+                break;
+            }
+            let line_start = offset;
+            offset += text.len();
+
+            let content = text.trim_start_matches([' ', '\t']);
+            let current = text.len() - content.len();
+            let pos = line_start + current;
+
+            let desired = if content.trim_end().is_empty() {
+                if blank_line != Some(line) {
+                    continue;
+                }
+                let depth = self
+                    .brackets
+                    .iter()
+                    .filter(|span| span.start() < pos && pos < span.end())
+                    .count();
+                depth * INDENT_WIDTH
+            } else if let Some(indent) = self.indent_at(pos) {
+                shift = indent as isize - current as isize;
+                indent
+            } else {
+                (current as isize + shift).max(0) as usize
+            };
+
+            if line >= first_line && desired != current {
+                edits.push(TextEdit {
+                    range: Range::new(Position::new(line, 0), Position::new(line, current as u32)),
+                    new_text: " ".repeat(desired),
+                });
+            }
+        }
+        edits
+    }
+}
+
+pub async fn range_formatting(
+    context: &RequestContext,
+    params: DocumentRangeFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let current_file = context.analyzer.analyze_file(&path, context.request_time)?;
+    // A range ending at the start of a line does not include that line.
+    let range = params.range;
+    let last_line = if range.end.line > range.start.line && range.end.character == 0 {
+        range.end.line - 1
+    } else {
+        range.end.line
+    };
+
+    let indenter = Indenter::new(&current_file);
+    Ok(Some(indenter.reindent(range.start.line, last_line, None)))
+}
+
+pub async fn on_type_formatting(
+    context: &RequestContext,
+    params: DocumentOnTypeFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+    let path = get_text_document_path(&params.text_document_position.text_document)?;
+    let current_file = context.analyzer.analyze_file(&path, context.request_time)?;
+    let line = params.text_document_position.position.line;
+
+    let indenter = Indenter::new(&current_file);
+    let edits = match params.ch.as_str() {
+        // Re-indent the line of the closing bracket.
+        "}" => Some(indenter.reindent(line, line, None)),
+        // Indent the new line.
+        "\n" => Some(indenter.reindent(line, line, Some(line))),
+        _ => None,
+    };
+    Ok(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        FormattingOptions, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+    };
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    fn text_document() -> TextDocumentIdentifier {
+        TextDocumentIdentifier {
+            uri: Url::from_file_path(testdata("workspaces/formatting/BUILD.gn")).unwrap(),
+        }
+    }
+
+    /// Summarizes edits as lines and new indentation widths.
+    fn indents(edits: Option<Vec<TextEdit>>) -> Vec<(u32, usize)> {
+        edits
+            .unwrap()
+            .into_iter()
+            .map(|edit| {
+                assert_eq!(edit.range.start.line, edit.range.end.line);
+                (edit.range.start.line, edit.new_text.len())
+            })
+            .collect()
+    }
+
+    async fn format_range(context: &RequestContext, range: Range) -> Vec<(u32, usize)> {
+        indents(
+            range_formatting(
+                context,
+                DocumentRangeFormattingParams {
+                    text_document: text_document(),
+                    range,
+                    options: FormattingOptions::default(),
+                    work_done_progress_params: Default::default(),
+                },
+            )
+            .await
+            .unwrap(),
+        )
+    }
+
+    async fn format_on_type(
+        context: &RequestContext,
+        position: Position,
+        ch: &str,
+    ) -> Vec<(u32, usize)> {
+        indents(
+            on_type_formatting(
+                context,
+                DocumentOnTypeFormattingParams {
+                    text_document_position: TextDocumentPositionParams {
+                        text_document: text_document(),
+                        position,
+                    },
+                    ch: ch.to_string(),
+                    options: FormattingOptions::default(),
+                },
+            )
+            .await
+            .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_range_formatting() {
+        let context = RequestContext::new_for_testing(Some(&testdata("workspaces/formatting")));
+        let lines =
+            |first: u32, last: u32| Range::new(Position::new(first, 0), Position::new(last, 1));
+
+        // Only lines in the range are re-indented.
+        assert_eq!(format_range(&context, lines(16, 16)).await, [(16, 4)]);
+        assert_eq!(
+            format_range(&context, lines(19, 21)).await,
+            [(19, 2), (20, 2), (21, 16)]
+        );
+
+        // A continuation line is shifted with the line it continues.
+        assert_eq!(format_range(&context, lines(21, 21)).await, [(21, 16)]);
+
+        // A range ending at the start of a line excludes it.
+        assert_eq!(
+            format_range(
+                &context,
+                Range::new(Position::new(18, 0), Position::new(19, 0))
+            )
+            .await,
+            [(18, 2)]
+        );
+
+        // Top-level lines are kept.
+        assert_eq!(format_range(&context, lines(14, 14)).await, []);
+    }
+
+    #[tokio::test]
+    async fn test_on_type_formatting() {
+        let context = RequestContext::new_for_testing(Some(&testdata("workspaces/formatting")));
+
+        // Only the line where the character is typed is re-indented.
+        assert_eq!(
+            format_on_type(&context, Position::new(18, 5), "}").await,
+            [(18, 2)]
+        );
+        assert_eq!(
+            format_on_type(&context, Position::new(22, 1), "}").await,
+            []
+        );
+
+        // The new line is indented.
+        assert_eq!(
+            format_on_type(&context, Position::new(24, 0), "\n").await,
+            [(24, 2)]
+        );
+    }
+}
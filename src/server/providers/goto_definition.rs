@@ -13,22 +13,44 @@
 // limitations under the License.
 
 use either::Either;
+use pest::Span;
 use tower_lsp::lsp_types::{
     GotoDefinitionParams, GotoDefinitionResponse, Location, LocationLink, Position, Range, Url,
 };
 
 use crate::{
-    analyzer::AnalyzedLink,
+    analyzer::{AnalyzedFile, AnalyzedLink, VariableAssignment},
     common::error::Result,
     parser::Node,
     server::{
         providers::utils::{
-            find_target, get_text_document_path, lookup_identifier_at, lookup_target_name_string_at,
+            find_target, get_text_document_path, lookup_identifier_at, lookup_scope_member_at,
+            lookup_target_name_string_at,
         },
         RequestContext,
     },
 };
 
+pub fn variable_assignment_link(
+    current_file: &AnalyzedFile,
+    origin: Span,
+    assignment: &VariableAssignment,
+) -> LocationLink {
+    let span = match &assignment.assignment_or_call {
+        Either::Left(assignment) => assignment.span,
+        Either::Right(call) => call.span,
+    };
+    LocationLink {
+        origin_selection_range: Some(current_file.document.line_index.range(origin)),
+        target_uri: Url::from_file_path(&assignment.document.path).unwrap(),
+        target_range: assignment.document.line_index.range(span),
+        target_selection_range: assignment
+            .document
+            .line_index
+            .range(assignment.primary_variable),
+    }
+}
+
 pub async fn goto_definition(
     context: &RequestContext,
     params: GotoDefinitionParams,
@@ -90,6 +112,28 @@ pub async fn goto_definition(
         })));
     }
 
+    // Check scope members.
+    if let Some(scope_access) = lookup_scope_member_at(&current_file, pos) {
+        let members = context.analyzer.workspace_for(&path)?.analyze_scope_at(
+            &current_file,
+            pos,
+            scope_access.scope.name,
+            context.request_time,
+        );
+        // Unknown members fall through as in hover, e.g. to variables of the
+        // same name for `invoker.sources`.
+        if let Some(member) = members.get().variables.get(scope_access.member.name) {
+            let links = member
+                .assignments
+                .iter()
+                .map(|assignment| {
+                    variable_assignment_link(&current_file, scope_access.member.span, assignment)
+                })
+                .collect();
+            return Ok(Some(GotoDefinitionResponse::Link(links)));
+        }
+    }
+
     let Some(ident) = lookup_identifier_at(&current_file, pos) else {
         return Ok(None);
     };
@@ -115,22 +159,150 @@ pub async fn goto_definition(
 
     // Check variables.
     if let Some(variable) = environment.get().variables.get(ident.name) {
-        links.extend(variable.assignments.iter().map(|assignment| {
-            let span = match &assignment.assignment_or_call {
-                Either::Left(assignment) => assignment.span,
-                Either::Right(call) => call.span,
-            };
-            LocationLink {
-                origin_selection_range: Some(current_file.document.line_index.range(ident.span)),
-                target_uri: Url::from_file_path(&assignment.document.path).unwrap(),
-                target_range: assignment.document.line_index.range(span),
-                target_selection_range: assignment
-                    .document
-                    .line_index
-                    .range(assignment.primary_variable),
-            }
-        }))
+        links.extend(
+            variable
+                .assignments
+                .iter()
+                .map(|assignment| variable_assignment_link(&current_file, ident.span, assignment)),
+        );
     }
 
     Ok(Some(GotoDefinitionResponse::Link(links)))
 }
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{TextDocumentIdentifier, TextDocumentPositionParams};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    async fn run_goto_definition(
+        context: &RequestContext,
+        path: &str,
+        position: Position,
+    ) -> Vec<(Url, Range)> {
+        let response = goto_definition(
+            context,
+            GotoDefinitionParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(testdata(path)).unwrap(),
+                    },
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap();
+        let Some(GotoDefinitionResponse::Link(links)) = response else {
+            panic!("unexpected response: {response:?}");
+        };
+        links
+            .into_iter()
+            .map(|link| (link.target_uri, link.target_selection_range))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_goto_definition_scope_members() {
+        let root = testdata("workspaces/scope");
+        let context = RequestContext::new_for_testing(Some(&root));
+        let build_uri = Url::from_file_path(root.join("BUILD.gn")).unwrap();
+
+        // A member of a block literal.
+        assert_eq!(
+            run_goto_definition(&context, "workspaces/scope/BUILD.gn", Position::new(30, 17)).await,
+            [(
+                build_uri.clone(),
+                Range::new(Position::new(18, 2), Position::new(18, 12))
+            )]
+        );
+
+        // A member of a scope read from a file.
+        assert_eq!(
+            run_goto_definition(&context, "workspaces/scope/BUILD.gn", Position::new(30, 42)).await,
+            [(
+                Url::from_file_path(root.join("values.scope")).unwrap(),
+                Range::new(Position::new(14, 0), Position::new(14, 6))
+            )]
+        );
+
+        // A member of invoker, set by the template invocation.
+        context
+            .analyzer
+            .analyze_file(&root.join("BUILD.gn"), context.request_time)
+            .unwrap();
+        assert_eq!(
+            run_goto_definition(
+                &context,
+                "workspaces/scope/templates.gni",
+                Position::new(16, 16)
+            )
+            .await,
+            [(
+                build_uri,
+                Range::new(Position::new(27, 2), Position::new(27, 8))
+            )]
+        );
+
+        // Unknown members fall through to variables of the same name.
+        assert_eq!(
+            run_goto_definition(
+                &context,
+                "workspaces/scope/templates.gni",
+                Position::new(24, 22)
+            )
+            .await,
+            [(
+                Url::from_file_path(root.join("templates.gni")).unwrap(),
+                Range::new(Position::new(22, 2), Position::new(22, 9))
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_goto_definition_wildcard_forward() {
+        let root = testdata("workspaces/forward_variables");
+        let context = RequestContext::new_for_testing(Some(&root));
+
+        // A template parameter forwarded by "*" is defined by the call.
+        assert_eq!(
+            run_goto_definition(
+                &context,
+                "workspaces/forward_variables/BUILD.gn",
+                Position::new(18, 18)
+            )
+            .await,
+            [(
+                Url::from_file_path(root.join("BUILD.gn")).unwrap(),
+                Range::new(Position::new(16, 35), Position::new(16, 36))
+            )]
+        );
+
+        // Parameters excluded by a list defined in BUILDCONFIG.gn are not.
+        assert_eq!(
+            run_goto_definition(
+                &context,
+                "workspaces/forward_variables/BUILD.gn",
+                Position::new(35, 10)
+            )
+            .await,
+            []
+        );
+
+        // Nor are those excluded by a list assigned in the template.
+        assert_eq!(
+            run_goto_definition(
+                &context,
+                "workspaces/forward_variables/BUILD.gn",
+                Position::new(44, 10)
+            )
+            .await,
+            []
+        );
+    }
+}
@@ -17,7 +17,7 @@ use tower_lsp::lsp_types::{Hover, HoverContents, HoverParams, MarkedString};
 use crate::{
     common::{builtins::BUILTINS, error::Result},
     server::{
-        providers::utils::{get_text_document_path, lookup_identifier_at},
+        providers::utils::{get_text_document_path, lookup_identifier_at, lookup_scope_member_at},
         RequestContext,
     },
 };
@@ -34,6 +34,35 @@ pub async fn hover(context: &RequestContext, params: HoverParams) -> Result<Opti
         return Ok(None);
     };
 
+    // Check scope members.
+    if let Some(scope_access) = lookup_scope_member_at(&current_file, pos) {
+        let members = context.analyzer.workspace_for(&path)?.analyze_scope_at(
+            &current_file,
+            pos,
+            scope_access.scope.name,
+            context.request_time,
+        );
+        // Unknown members fall through, e.g. to builtin variables for
+        // `invoker.sources`.
+        if let Some(member) = members.get().variables.get(scope_access.member.name) {
+            return Ok(Some(Hover {
+                contents: HoverContents::Array(
+                    member
+                        .format_help(&current_file.workspace_root)
+                        .into_iter()
+                        .map(MarkedString::from_markdown)
+                        .collect(),
+                ),
+                range: Some(
+                    current_file
+                        .document
+                        .line_index
+                        .range(scope_access.member.span),
+                ),
+            }));
+        }
+    }
+
     let Some(ident) = lookup_identifier_at(&current_file, pos) else {
         return Ok(None);
     };
@@ -137,4 +166,123 @@ mod tests {
             })
         );
     }
+
+    #[tokio::test]
+    async fn test_hover_args_override() {
+        let root = testdata("workspaces/build_dir");
+        let context = RequestContext::new_for_testing(Some(&root));
+        context
+            .analyzer
+            .workspace_for(&root.join("BUILD.gn"))
+            .unwrap()
+            .set_active_build_dir(Some(root.join("out/Release")));
+
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(root.join("BUILD.gn")).unwrap(),
+                },
+                position: Position {
+                    line: 14,
+                    character: 4,
+                },
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let Some(Hover {
+            contents: HoverContents::Array(contents),
+            ..
+        }) = hover(&context, params).await.unwrap()
+        else {
+            panic!("unexpected hover");
+        };
+
+        let args_uri = Url::from_file_path(root.join("out/Release/args.gn")).unwrap();
+        assert_eq!(
+            contents.last(),
+            Some(&MarkedString::from_markdown(format!(
+                "Set in [//out/Release/args.gn:15:1]({args_uri}#L15,1) of the active build \
+                 directory:\n```gn\nis_debug = false\n```"
+            )))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hover_scope_member() {
+        let root = testdata("workspaces/scope");
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(root.join("BUILD.gn")).unwrap(),
+                },
+                position: Position {
+                    line: 30,
+                    character: 20,
+                },
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let Some(Hover {
+            contents: HoverContents::Array(contents),
+            range,
+        }) = hover(&RequestContext::new_for_testing(Some(&root)), params)
+            .await
+            .unwrap()
+        else {
+            panic!("unexpected hover");
+        };
+
+        assert_eq!(
+            contents[..2],
+            [
+                MarkedString::from_markdown("```gn\nenable_foo = true\n```".to_string()),
+                MarkedString::from_markdown("```text\nWhether to enable foo.\n```".to_string()),
+            ]
+        );
+        assert_eq!(
+            range,
+            Some(Range {
+                start: Position {
+                    line: 30,
+                    character: 17,
+                },
+                end: Position {
+                    line: 30,
+                    character: 27,
+                },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hover_template_parameters() {
+        let root = testdata("workspaces/template_parameters");
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(root.join("BUILD.gn")).unwrap(),
+                },
+                position: Position {
+                    line: 16,
+                    character: 0,
+                },
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let Some(Hover {
+            contents: HoverContents::Array(contents),
+            ..
+        }) = hover(&RequestContext::new_for_testing(Some(&root)), params)
+            .await
+            .unwrap()
+        else {
+            panic!("unexpected hover");
+        };
+
+        assert!(contents.contains(&MarkedString::from_markdown(
+            "Parameters:\n- `sources` (required)\n- `deps`\n- `public_deps`\n- `extra_flags`\n\
+             - `output_name` (read unconditionally)"
+                .to_string()
+        )));
+    }
 }
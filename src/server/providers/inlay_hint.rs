@@ -0,0 +1,246 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use itertools::Itertools;
+use normalize_path::NormalizePath;
+use pest::Span;
+use tower_lsp::lsp_types::{InlayHint, InlayHintLabel, InlayHintParams};
+
+use crate::{
+    analyzer::{AnalyzedFile, AnalyzedLink},
+    common::error::Result,
+    parser::{string_content_span, Node},
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+/// Formats a directory in the workspace as a source-absolute path, e.g.
+/// "//base/util".
+fn format_source_dir(dir: &Path, workspace_root: &Path) -> Option<String> {
+    let relative = dir.strip_prefix(workspace_root).ok()?;
+    Some(format!(
+        "//{}",
+        relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .join("/")
+    ))
+}
+
+/// Returns absolute labels for relative target labels, e.g. "//base:foo" for
+/// ":foo" in //base/BUILD.gn.
+fn label_hints<'i>(file: &'i AnalyzedFile) -> Vec<(Span<'i>, String)> {
+    file.link_index
+        .get()
+        .values()
+        .flatten()
+        .filter_map(|link| {
+            let AnalyzedLink::Target { path, name, span } = link else {
+                return None;
+            };
+            if string_content_span(*span).as_str().starts_with("//") {
+                return None;
+            }
+            let dir = format_source_dir(path.parent()?, &file.workspace_root)?;
+            Some((*span, format!("{dir}:{name}")))
+        })
+        .collect()
+}
+
+/// Returns directories that builtin variables and functions evaluate to.
+/// Output directories are concrete only if the active build directory is
+/// known.
+fn directory_hints<'i>(
+    file: &'i AnalyzedFile,
+    build_dir: Option<&Path>,
+) -> Vec<(Span<'i>, String)> {
+    let current_dir = file.document.path.parent().unwrap();
+    let Some(source_dir) = format_source_dir(current_dir, &file.workspace_root) else {
+        return Vec::new();
+    };
+    let relative_dir = source_dir.trim_start_matches('/');
+    let (root_gen_dir, root_obj_dir) =
+        match build_dir.and_then(|build_dir| format_source_dir(build_dir, &file.workspace_root)) {
+            Some(root_out_dir) => (format!("{root_out_dir}/gen"), format!("{root_out_dir}/obj")),
+            None => ("$root_gen_dir".to_string(), "$root_out_dir/obj".to_string()),
+        };
+    let root = file.parsed_root.get();
+
+    let members: Vec<usize> = root
+        .walk()
+        .filter_map(|node| node.as_scope_access())
+        .map(|scope_access| scope_access.member.span.start())
+        .collect();
+    let mut hints: Vec<(Span, String)> = root
+        .identifiers()
+        .filter(|ident| !members.contains(&ident.span.start()))
+        .filter_map(|ident| {
+            let value = match ident.name {
+                "target_gen_dir" => [root_gen_dir.as_str(), relative_dir].join("/"),
+                "target_out_dir" => [root_obj_dir.as_str(), relative_dir].join("/"),
+                _ => return None,
+            };
+            Some((ident.span, value.trim_end_matches('/').to_string()))
+        })
+        .collect();
+
+    hints.extend(root.calls().filter_map(|call| {
+        if call.function.name != "get_path_info" {
+            return None;
+        }
+        let [input, what] = call.args.as_slice() else {
+            return None;
+        };
+        if what.as_simple_string() != Some("dir") {
+            return None;
+        }
+        let input = input.as_simple_string()?;
+        if input.starts_with("//") {
+            return None;
+        }
+        let path = current_dir.join(input).normalize();
+        let dir = if input.ends_with('/') {
+            path.as_path()
+        } else {
+            path.parent()?
+        };
+        Some((call.span, format_source_dir(dir, &file.workspace_root)?))
+    }));
+
+    hints
+}
+
+pub async fn inlay_hint(
+    context: &RequestContext,
+    params: InlayHintParams,
+) -> Result<Option<Vec<InlayHint>>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let current_file = context.analyzer.analyze_file(&path, context.request_time)?;
+    // Relative labels and directories depend on the directory of the BUILD.gn
+    // file being evaluated, which is unknown in imported files.
+    if path.file_name().is_none_or(|name| name != "BUILD.gn") {
+        return Ok(Some(Vec::new()));
+    }
+    let line_index = &current_file.document.line_index;
+    let build_dir = context
+        .analyzer
+        .workspace_for(&current_file.workspace_root)
+        .ok()
+        .and_then(|workspace| workspace.active_build_dir());
+
+    let mut hints: Vec<InlayHint> = label_hints(&current_file)
+        .into_iter()
+        .chain(directory_hints(&current_file, build_dir.as_deref()))
+        .map(|(span, label)| InlayHint {
+            position: line_index.position(span.end()),
+            label: InlayHintLabel::String(label),
+            kind: None,
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(true),
+            padding_right: None,
+            data: None,
+        })
+        .filter(|hint| params.range.start <= hint.position && hint.position <= params.range.end)
+        .collect();
+    hints.sort_by_key(|hint| hint.position);
+
+    Ok(Some(hints))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{Position, Range, TextDocumentIdentifier, Url};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    async fn run_inlay_hint(context: &RequestContext, path: &str) -> Vec<(Position, String)> {
+        let hints = inlay_hint(
+            context,
+            InlayHintParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(testdata(path)).unwrap(),
+                },
+                range: Range::new(Position::new(0, 0), Position::new(100, 0)),
+                work_done_progress_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        hints
+            .into_iter()
+            .map(|hint| {
+                let InlayHintLabel::String(label) = hint.label else {
+                    panic!("unexpected label: {:?}", hint.label);
+                };
+                (hint.position, label)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_inlay_hint() {
+        let context = RequestContext::new_for_testing(Some(&testdata("workspaces/inlay_hint")));
+        assert_eq!(
+            run_inlay_hint(&context, "workspaces/inlay_hint/base/util/BUILD.gn").await,
+            [
+                (Position::new(19, 10), "//base/util:foo".to_string()),
+                (Position::new(21, 13), "//base:base".to_string()),
+                (Position::new(23, 30), "$root_gen_dir/base/util".to_string()),
+                (
+                    Position::new(23, 53),
+                    "$root_out_dir/obj/base/util".to_string()
+                ),
+                (Position::new(24, 46), "//base/sub".to_string()),
+            ]
+        );
+
+        // Imported files are evaluated in the directory of the importer.
+        assert_eq!(
+            run_inlay_hint(&context, "workspaces/inlay_hint/base/util/util.gni").await,
+            []
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inlay_hint_active_build_dir() {
+        let root = testdata("workspaces/inlay_hint");
+        let context = RequestContext::new_for_testing(Some(&root));
+        context
+            .analyzer
+            .workspace_for(&root)
+            .unwrap()
+            .set_active_build_dir(Some(root.join("out/Release")));
+
+        let hints = run_inlay_hint(&context, "workspaces/inlay_hint/base/util/BUILD.gn").await;
+        assert_eq!(
+            hints[2..4],
+            [
+                (
+                    Position::new(23, 30),
+                    "//out/Release/gen/base/util".to_string()
+                ),
+                (
+                    Position::new(23, 53),
+                    "//out/Release/obj/base/util".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -12,17 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod build_dir;
+pub mod call_hierarchy;
 pub mod code_action;
 pub mod code_lens;
 pub mod completion;
 pub mod configuration;
+pub mod declaration;
 pub mod diagnostics;
 pub mod document;
+pub mod document_highlight;
 pub mod document_link;
 pub mod document_symbol;
+pub mod execute_command;
+pub mod folding_range;
 pub mod formatting;
 pub mod goto_definition;
 pub mod hover;
+pub mod inlay_hint;
 pub mod references;
+pub mod rename;
+pub mod selection_range;
+pub mod semantic_tokens;
+pub mod signature_help;
 pub mod utils;
 pub mod workspace_symbol;
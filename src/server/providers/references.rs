@@ -12,17 +12,353 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use tower_lsp::lsp_types::{Location, ReferenceParams, Url};
+use std::{collections::HashSet, path::PathBuf, sync::Arc, time::Instant};
+
+use either::Either;
+use pest::Span;
+use tower_lsp::lsp_types::{Location, ReferenceParams, TextEdit, Url};
 
 use crate::{
-    analyzer::{AnalyzedBlock, AnalyzedFile, AnalyzedLink, WorkspaceAnalyzer},
-    common::error::Result,
+    analyzer::{
+        AnalyzedBlock, AnalyzedFile, AnalyzedLink, OwnedEnvironment, Template, Variable,
+        VariableAssignment, WorkspaceAnalyzer,
+    },
+    common::{builtins::TEMPLATE, error::Result},
+    parser::{string_content_span, Identifier, Node, Statement},
     server::{
-        providers::utils::{get_text_document_path, lookup_target_name_string_at},
+        providers::utils::{
+            get_text_document_path, lookup_identifier_at, lookup_target_name_string_at,
+        },
         RequestContext,
     },
 };
 
+/// Identifies a definition by its file and the start of the defining statement.
+type DefinitionKey = (PathBuf, usize);
+
+fn assignment_key(assignment: &VariableAssignment) -> DefinitionKey {
+    let span = match &assignment.assignment_or_call {
+        Either::Left(assignment) => assignment.span,
+        Either::Right(call) => call.span,
+    };
+    (assignment.document.path.clone(), span.start())
+}
+
+fn variable_keys(variable: &Variable) -> HashSet<DefinitionKey> {
+    variable.assignments.iter().map(assignment_key).collect()
+}
+
+fn template_key(template: &Template) -> DefinitionKey {
+    (template.document.path.clone(), template.call.span.start())
+}
+
+/// A variable or template binding, identified by its definitions.
+#[derive(Clone, Debug)]
+pub enum Symbol {
+    Variable {
+        name: String,
+        definitions: HashSet<DefinitionKey>,
+    },
+    Template {
+        name: String,
+        definition: DefinitionKey,
+    },
+}
+
+impl Symbol {
+    pub fn name(&self) -> &str {
+        match self {
+            Symbol::Variable { name, .. } | Symbol::Template { name, .. } => name,
+        }
+    }
+}
+
+/// An occurrence of a symbol in a file.
+pub struct SymbolOccurrence<'i> {
+    pub span: Span<'i>,
+    /// Whether the occurrence defines or assigns to the symbol.
+    pub is_write: bool,
+}
+
+/// Resolves identifiers in a file to the definitions they refer to.
+struct SymbolResolver<'a> {
+    file: &'a AnalyzedFile,
+    imports: OwnedEnvironment,
+    functions: HashSet<usize>,
+    members: HashSet<usize>,
+    writes: HashSet<usize>,
+}
+
+impl<'a> SymbolResolver<'a> {
+    fn new(
+        workspace: &WorkspaceAnalyzer,
+        file: &'a Arc<AnalyzedFile>,
+        request_time: Instant,
+    ) -> Self {
+        let root = file.parsed_root.get();
+        let functions = root
+            .calls()
+            .map(|call| call.function.span.start())
+            .collect();
+        let members = root
+            .walk()
+            .filter_map(|node| node.as_scope_access())
+            .map(|scope_access| scope_access.member.span.start())
+            .collect();
+        let writes = root
+            .walk()
+            .filter_map(|node| match node.as_statement()? {
+                Statement::Assignment(assignment) => {
+                    Some(assignment.lvalue.primary_identifier().span.start())
+                }
+                _ => None,
+            })
+            .collect();
+        Self {
+            file,
+            imports: workspace.analyze_imports(file, request_time),
+            functions,
+            members,
+            writes,
+        }
+    }
+
+    fn is_function(&self, ident: &Identifier) -> bool {
+        self.functions.contains(&ident.span.start())
+    }
+
+    fn is_member(&self, ident: &Identifier) -> bool {
+        self.members.contains(&ident.span.start())
+    }
+
+    fn resolve_variable(&self, ident: &Identifier) -> HashSet<DefinitionKey> {
+        let imported = self
+            .imports
+            .get()
+            .variables
+            .get(ident.name)
+            .map(variable_keys)
+            .unwrap_or_default();
+
+        let local_variables = self.file.local_variables_at(ident.span.start());
+        let Some(variable) = local_variables.get(ident.name) else {
+            return imported;
+        };
+
+        let mut definitions = variable_keys(variable);
+
+        // Top-level assignments modify the variable imported from other files.
+        let top_level = self
+            .file
+            .exports
+            .get()
+            .variables
+            .get(ident.name)
+            .map(variable_keys)
+            .unwrap_or_default();
+        if !definitions.is_disjoint(&top_level) {
+            definitions.extend(imported);
+        }
+
+        definitions
+    }
+
+    fn resolve_template(&self, name: &str, pos: usize) -> Option<DefinitionKey> {
+        self.file
+            .local_templates_at(pos)
+            .get(name)
+            .or_else(|| self.imports.get().templates.get(name))
+            .map(template_key)
+    }
+
+    fn lookup_symbol_at(&self, pos: usize) -> Option<(Symbol, Span<'a>)> {
+        let root = self.file.parsed_root.get();
+
+        // Check template definitions.
+        for call in root.calls() {
+            if call.function.name != TEMPLATE {
+                continue;
+            }
+            let Some(string) = call.only_arg().and_then(|expr| expr.as_primary_string()) else {
+                continue;
+            };
+            if !(string.span.start() < pos && pos < string.span.end()) {
+                continue;
+            }
+            let name = call.only_arg().and_then(|expr| expr.as_simple_string())?;
+            let span = string_content_span(string.span);
+            let symbol = Symbol::Template {
+                name: name.to_string(),
+                definition: (self.file.document.path.clone(), call.span.start()),
+            };
+            return Some((symbol, span));
+        }
+
+        let ident = lookup_identifier_at(self.file, pos)?;
+        if self.is_member(ident) {
+            return None;
+        }
+        let symbol = if self.is_function(ident) {
+            Symbol::Template {
+                name: ident.name.to_string(),
+                definition: self.resolve_template(ident.name, ident.span.start())?,
+            }
+        } else {
+            let definitions = self.resolve_variable(ident);
+            if definitions.is_empty() {
+                return None;
+            }
+            Symbol::Variable {
+                name: ident.name.to_string(),
+                definitions,
+            }
+        };
+        Some((symbol, ident.span))
+    }
+
+    fn find_occurrences(&self, symbol: &Symbol) -> Vec<SymbolOccurrence<'a>> {
+        let root = self.file.parsed_root.get();
+        let mut occurrences: Vec<SymbolOccurrence> = Vec::new();
+
+        match symbol {
+            Symbol::Variable { name, definitions } => {
+                for ident in root.identifiers() {
+                    if ident.name != name || self.is_function(ident) || self.is_member(ident) {
+                        continue;
+                    }
+                    if self.resolve_variable(ident).is_disjoint(definitions) {
+                        continue;
+                    }
+                    occurrences.push(SymbolOccurrence {
+                        span: ident.span,
+                        is_write: self.writes.contains(&ident.span.start()),
+                    });
+                }
+            }
+            Symbol::Template { name, definition } => {
+                for call in root.calls() {
+                    if call.function.name == TEMPLATE {
+                        if (self.file.document.path.clone(), call.span.start()) != *definition {
+                            continue;
+                        }
+                        let Some(string) =
+                            call.only_arg().and_then(|expr| expr.as_primary_string())
+                        else {
+                            continue;
+                        };
+                        occurrences.push(SymbolOccurrence {
+                            span: string_content_span(string.span),
+                            is_write: true,
+                        });
+                    } else if call.function.name == name
+                        && self
+                            .resolve_template(name, call.function.span.start())
+                            .as_ref()
+                            == Some(definition)
+                    {
+                        occurrences.push(SymbolOccurrence {
+                            span: call.function.span,
+                            is_write: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        occurrences
+    }
+
+    fn find_references(&self, symbol: &Symbol, include_definitions: bool) -> Vec<Location> {
+        let uri = Url::from_file_path(&self.file.document.path).unwrap();
+        self.find_occurrences(symbol)
+            .into_iter()
+            .filter(|occurrence| include_definitions || !occurrence.is_write)
+            .map(|occurrence| Location {
+                uri: uri.clone(),
+                range: self.file.document.line_index.range(occurrence.span),
+            })
+            .collect()
+    }
+}
+
+/// Returns the variable or template at the position, along with the span of
+/// its name.
+pub fn lookup_symbol_at<'a>(
+    workspace: &WorkspaceAnalyzer,
+    file: &'a Arc<AnalyzedFile>,
+    pos: usize,
+    request_time: Instant,
+) -> Option<(Symbol, Span<'a>)> {
+    SymbolResolver::new(workspace, file, request_time).lookup_symbol_at(pos)
+}
+
+/// Returns the occurrences of the symbol in the file.
+pub fn symbol_occurrences<'a>(
+    workspace: &WorkspaceAnalyzer,
+    file: &'a Arc<AnalyzedFile>,
+    symbol: &Symbol,
+    request_time: Instant,
+) -> Vec<SymbolOccurrence<'a>> {
+    SymbolResolver::new(workspace, file, request_time).find_occurrences(symbol)
+}
+
+/// Returns whether the symbol is visible from other files, i.e. defined at the
+/// top level of a file and not private.
+pub fn is_exported_symbol(
+    workspace: &WorkspaceAnalyzer,
+    symbol: &Symbol,
+    request_time: Instant,
+) -> bool {
+    match symbol {
+        Symbol::Variable { name, definitions } => definitions.iter().any(|(path, _)| {
+            workspace
+                .analyze_file(path, request_time)
+                .exports
+                .get()
+                .variables
+                .get(name.as_str())
+                .is_some_and(|variable| !variable_keys(variable).is_disjoint(definitions))
+        }),
+        Symbol::Template { name, definition } => workspace
+            .analyze_file(&definition.0, request_time)
+            .exports
+            .get()
+            .templates
+            .get(name.as_str())
+            .is_some_and(|template| &template_key(template) == definition),
+    }
+}
+
+/// Finds all uses of the symbol, optionally including its definitions and
+/// assignments. Exported symbols are searched in all files in the workspace.
+pub async fn symbol_references(
+    workspace: &WorkspaceAnalyzer,
+    current_file: &Arc<AnalyzedFile>,
+    symbol: &Symbol,
+    include_definitions: bool,
+    request_time: Instant,
+) -> Vec<Location> {
+    let mut files = vec![current_file.clone()];
+    if is_exported_symbol(workspace, symbol, request_time) {
+        files.extend(
+            workspace
+                .scan_files()
+                .await
+                .into_iter()
+                .filter(|file| !file.external && file.document.path != current_file.document.path),
+        );
+    }
+
+    files
+        .iter()
+        .filter(|file| file.document.data.contains(symbol.name()))
+        .flat_map(|file| {
+            SymbolResolver::new(workspace, file, request_time)
+                .find_references(symbol, include_definitions)
+        })
+        .collect()
+}
+
 fn get_overlapping_targets<'p>(root: &AnalyzedBlock<'p>, prefix: &str) -> Vec<&'p str> {
     root.targets()
         .filter(|target| target.name.len() > prefix.len() && target.name.starts_with(prefix))
@@ -30,16 +366,18 @@ fn get_overlapping_targets<'p>(root: &AnalyzedBlock<'p>, prefix: &str) -> Vec<&'
         .collect()
 }
 
-pub async fn target_references(
+/// Calls `f` with every label string in the workspace pointing to the target,
+/// along with the target name parsed from the label.
+async fn visit_target_links(
     workspace: &WorkspaceAnalyzer,
     current_file: &AnalyzedFile,
     target_name: &str,
-) -> Result<Vec<Location>> {
+    mut f: impl FnMut(&AnalyzedFile, &str, Span),
+) {
     let bad_prefixes = get_overlapping_targets(current_file.analyzed_root.get(), target_name);
 
     let files = workspace.scan_files().await;
 
-    let mut references: Vec<Location> = Vec::new();
     for file in files {
         let Some(links) = file.link_index.get().get(&current_file.document.path) else {
             continue;
@@ -57,16 +395,68 @@ pub async fn target_references(
             if !name.starts_with(target_name) {
                 continue;
             }
-            references.push(Location {
-                uri: Url::from_file_path(&file.document.path).unwrap(),
-                range: file.document.line_index.range(*span),
-            });
+            f(&file, name, *span);
         }
     }
+}
 
+pub async fn target_references(
+    workspace: &WorkspaceAnalyzer,
+    current_file: &AnalyzedFile,
+    target_name: &str,
+) -> Result<Vec<Location>> {
+    let mut references: Vec<Location> = Vec::new();
+    visit_target_links(workspace, current_file, target_name, |file, _, span| {
+        references.push(Location {
+            uri: Url::from_file_path(&file.document.path).unwrap(),
+            range: file.document.line_index.range(span),
+        });
+    })
+    .await;
     Ok(references)
 }
 
+/// Returns edits that make the labels pointing to the target use the new name.
+pub async fn target_label_edits(
+    workspace: &WorkspaceAnalyzer,
+    current_file: &AnalyzedFile,
+    target_name: &str,
+    new_name: &str,
+) -> Vec<(Url, TextEdit)> {
+    let mut edits: Vec<(Url, TextEdit)> = Vec::new();
+    visit_target_links(workspace, current_file, target_name, |file, name, span| {
+        // Allow a toolchain suffix only, e.g. "//foo:bar(//toolchain)".
+        let suffix = &name[target_name.len()..];
+        if !suffix.is_empty() && !suffix.starts_with('(') {
+            return;
+        }
+
+        let content = string_content_span(span);
+        let (start, end, new_text) = if let Some(colon) = content.as_str().find(':') {
+            let start = content.start() + colon + 1;
+            (start, start + target_name.len(), new_name.to_string())
+        } else {
+            // The name is implied by the directory, e.g. "//foo/bar". Keep the
+            // directory and append the new name.
+            let name_start = content.as_str().rfind('/').map_or(0, |slash| slash + 1);
+            let name_end = content.start() + name_start + target_name.len();
+            (name_end, name_end, format!(":{new_name}"))
+        };
+        edits.push((
+            Url::from_file_path(&file.document.path).unwrap(),
+            TextEdit {
+                range: file
+                    .document
+                    .line_index
+                    .range(Span::new(span.get_input(), start, end).unwrap()),
+                new_text,
+            },
+        ));
+    })
+    .await;
+    edits
+}
+
 pub async fn references(
     context: &RequestContext,
     params: ReferenceParams,
@@ -94,5 +484,110 @@ pub async fn references(
         ));
     };
 
-    Ok(None)
+    let Some((symbol, _)) = lookup_symbol_at(&workspace, &current_file, pos, context.request_time)
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(
+        symbol_references(
+            &workspace,
+            &current_file,
+            &symbol,
+            params.context.include_declaration,
+            context.request_time,
+        )
+        .await,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        Position, Range, ReferenceContext, TextDocumentIdentifier, TextDocumentPositionParams,
+    };
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    fn uri(name: &str) -> Url {
+        Url::from_file_path(testdata(format!("workspaces/references/{name}"))).unwrap()
+    }
+
+    async fn references_at(
+        name: &str,
+        line: u32,
+        character: u32,
+        include_declaration: bool,
+    ) -> Vec<(Url, Range)> {
+        let context = RequestContext::new_for_testing(Some(&testdata("workspaces/references")));
+
+        // Background indexing is disabled in tests, so load files to be searched.
+        for name in ["BUILD.gn", "sub/BUILD.gn", "other/BUILD.gn"] {
+            context
+                .analyzer
+                .analyze_file(&uri(name).to_file_path().unwrap(), context.request_time)
+                .unwrap();
+        }
+
+        let params = ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri(name) },
+                position: Position::new(line, character),
+            },
+            context: ReferenceContext {
+                include_declaration,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+
+        let mut locations: Vec<(Url, Range)> = references(&context, params)
+            .await
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|location| (location.uri, location.range))
+            .collect();
+        locations.sort_by_key(|(uri, range)| (uri.to_string(), range.start));
+        locations
+    }
+
+    fn make_range(line: u32, start: u32, end: u32) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[tokio::test]
+    async fn test_variable_references() {
+        let reads = [
+            (uri("BUILD.gn"), make_range(16, 4, 15)),
+            (uri("sub/BUILD.gn"), make_range(17, 12, 23)),
+        ];
+        assert_eq!(references_at("common.gni", 14, 2, false).await, reads);
+
+        let mut all = vec![(uri("common.gni"), make_range(14, 0, 11))];
+        all.extend(reads);
+        all.sort_by_key(|(uri, range)| (uri.to_string(), range.start));
+        assert_eq!(references_at("sub/BUILD.gn", 17, 15, true).await, all);
+    }
+
+    #[tokio::test]
+    async fn test_template_references() {
+        assert_eq!(
+            references_at("BUILD.gn", 17, 3, true).await,
+            [
+                (uri("BUILD.gn"), make_range(17, 2, 8)),
+                (uri("common.gni"), make_range(16, 10, 16)),
+                (uri("sub/BUILD.gn"), make_range(16, 0, 6)),
+            ]
+        );
+        assert_eq!(
+            references_at("common.gni", 16, 12, false).await,
+            [
+                (uri("BUILD.gn"), make_range(17, 2, 8)),
+                (uri("sub/BUILD.gn"), make_range(16, 0, 6)),
+            ]
+        );
+    }
 }
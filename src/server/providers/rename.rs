@@ -0,0 +1,443 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, time::Instant};
+
+use tower_lsp::lsp_types::{
+    PrepareRenameResponse, RenameParams, TextDocumentPositionParams, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::{
+    analyzer::{AnalyzedFile, Target, WorkspaceAnalyzer},
+    common::{
+        builtins::{BUILTINS, FORWARD_VARIABLES_FROM},
+        error::{Error, Result},
+        utils::is_exported,
+    },
+    parser::{string_content_span, Node},
+    server::{
+        providers::{
+            references::{
+                is_exported_symbol, lookup_symbol_at, symbol_references, target_label_edits, Symbol,
+            },
+            utils::{get_text_document_path, lookup_target_name_string_at},
+        },
+        RequestContext,
+    },
+};
+
+fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_valid_target_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, ':' | '/' | '(' | ')' | '"' | '$' | '\\'))
+}
+
+fn is_builtin(name: &str) -> bool {
+    BUILTINS.all().any(|symbol| symbol.name == name)
+}
+
+/// Returns whether the variable is defined by forward_variables_from, in which
+/// case its name is tied to the variable in the other scope.
+fn is_forwarded(workspace: &WorkspaceAnalyzer, symbol: &Symbol, request_time: Instant) -> bool {
+    let Symbol::Variable { definitions, .. } = symbol else {
+        return false;
+    };
+    definitions.iter().any(|(path, start)| {
+        workspace
+            .analyze_file(path, request_time)
+            .parsed_root
+            .get()
+            .calls()
+            .any(|call| call.span.start() == *start && call.function.name == FORWARD_VARIABLES_FROM)
+    })
+}
+
+/// Checks that a target can be renamed, before the new name is known.
+async fn check_target_renamable(context: &RequestContext) -> Result<()> {
+    // Labels in other files can be found only with background indexing.
+    if !context.client.configurations().await.background_indexing {
+        return Err(Error::General(
+            "Renaming targets requires background indexing".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that a symbol can be renamed, before the new name is known.
+async fn check_symbol_renamable(
+    context: &RequestContext,
+    workspace: &WorkspaceAnalyzer,
+    symbol: &Symbol,
+) -> Result<()> {
+    if is_builtin(symbol.name()) {
+        return Err(Error::General(format!(
+            "Cannot rename builtin symbol {}",
+            symbol.name()
+        )));
+    }
+    if is_forwarded(workspace, symbol, context.request_time) {
+        return Err(Error::General(format!(
+            "Cannot rename {} as it is defined by {}",
+            symbol.name(),
+            FORWARD_VARIABLES_FROM
+        )));
+    }
+    // Uses in other files can be found only with background indexing.
+    if is_exported_symbol(workspace, symbol, context.request_time)
+        && !context.client.configurations().await.background_indexing
+    {
+        return Err(Error::General(
+            "Renaming symbols shared across files requires background indexing".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn prepare_rename(
+    context: &RequestContext,
+    params: TextDocumentPositionParams,
+) -> Result<Option<PrepareRenameResponse>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let workspace = context.analyzer.workspace_for(&path)?;
+    let current_file = workspace.analyze_file(&path, context.request_time);
+
+    let Some(pos) = current_file.document.line_index.offset(params.position) else {
+        return Ok(None);
+    };
+
+    if let Some(target) = lookup_target_name_string_at(&current_file, pos) {
+        check_target_renamable(context).await?;
+        return Ok(Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: current_file
+                .document
+                .line_index
+                .range(string_content_span(target.call.args[0].span())),
+            placeholder: target.name.to_string(),
+        }));
+    }
+
+    let Some((symbol, span)) =
+        lookup_symbol_at(&workspace, &current_file, pos, context.request_time)
+    else {
+        return Ok(None);
+    };
+    check_symbol_renamable(context, &workspace, &symbol).await?;
+
+    Ok(Some(PrepareRenameResponse::RangeWithPlaceholder {
+        range: current_file.document.line_index.range(span),
+        placeholder: symbol.name().to_string(),
+    }))
+}
+
+async fn rename_target(
+    context: &RequestContext,
+    workspace: &WorkspaceAnalyzer,
+    current_file: &AnalyzedFile,
+    target: &Target<'_>,
+    new_name: &str,
+) -> Result<WorkspaceEdit> {
+    check_target_renamable(context).await?;
+    if !is_valid_target_name(new_name) {
+        return Err(Error::General(format!(
+            "{new_name} is not a valid target name"
+        )));
+    }
+    if new_name != target.name && current_file.exports.get().targets.contains_key(new_name) {
+        return Err(Error::General(format!(
+            "{new_name} is already defined in {}",
+            current_file.document.path.display()
+        )));
+    }
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    changes
+        .entry(Url::from_file_path(&current_file.document.path).unwrap())
+        .or_default()
+        .push(TextEdit {
+            range: current_file
+                .document
+                .line_index
+                .range(string_content_span(target.call.args[0].span())),
+            new_text: new_name.to_string(),
+        });
+    for (uri, edit) in target_label_edits(workspace, current_file, target.name, new_name).await {
+        changes.entry(uri).or_default().push(edit);
+    }
+
+    Ok(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    })
+}
+
+pub async fn rename(
+    context: &RequestContext,
+    params: RenameParams,
+) -> Result<Option<WorkspaceEdit>> {
+    let path = get_text_document_path(&params.text_document_position.text_document)?;
+    let workspace = context.analyzer.workspace_for(&path)?;
+    let current_file = workspace.analyze_file(&path, context.request_time);
+
+    let Some(pos) = current_file
+        .document
+        .line_index
+        .offset(params.text_document_position.position)
+    else {
+        return Ok(None);
+    };
+
+    if let Some(target) = lookup_target_name_string_at(&current_file, pos) {
+        return Ok(Some(
+            rename_target(
+                context,
+                &workspace,
+                &current_file,
+                &target,
+                &params.new_name,
+            )
+            .await?,
+        ));
+    }
+
+    let Some((symbol, _)) = lookup_symbol_at(&workspace, &current_file, pos, context.request_time)
+    else {
+        return Ok(None);
+    };
+    check_symbol_renamable(context, &workspace, &symbol).await?;
+    if !is_valid_identifier(&params.new_name) {
+        return Err(Error::General(format!(
+            "{} is not a valid identifier",
+            params.new_name
+        )));
+    }
+
+    if is_exported_symbol(&workspace, &symbol, context.request_time)
+        && !is_exported(&params.new_name)
+    {
+        return Err(Error::General(format!(
+            "{} would not be visible from files importing {}",
+            params.new_name,
+            symbol.name()
+        )));
+    }
+
+    let mut changes: HashMap<_, Vec<TextEdit>> = HashMap::new();
+    for reference in symbol_references(
+        &workspace,
+        &current_file,
+        &symbol,
+        true,
+        context.request_time,
+    )
+    .await
+    {
+        changes.entry(reference.uri).or_default().push(TextEdit {
+            range: reference.range,
+            new_text: params.new_name.clone(),
+        });
+    }
+
+    Ok(Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        Position, Range, TextDocumentIdentifier, Url, WorkDoneProgressParams,
+    };
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    fn uri(name: &str) -> Url {
+        Url::from_file_path(testdata(format!("workspaces/rename/{name}"))).unwrap()
+    }
+
+    fn test_context() -> RequestContext {
+        let context = RequestContext::new_for_testing(Some(&testdata("workspaces/rename")));
+
+        // Background indexing is disabled in tests, so load files to be searched.
+        for name in ["BUILD.gn", "sub/BUILD.gn"] {
+            context
+                .analyzer
+                .analyze_file(&uri(name).to_file_path().unwrap(), context.request_time)
+                .unwrap();
+        }
+        context
+    }
+
+    fn position_params(name: &str, line: u32, character: u32) -> TextDocumentPositionParams {
+        TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri(name) },
+            position: Position::new(line, character),
+        }
+    }
+
+    async fn try_rename_at(
+        name: &str,
+        line: u32,
+        character: u32,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>> {
+        let params = RenameParams {
+            text_document_position: position_params(name, line, character),
+            new_name: new_name.to_string(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        rename(&test_context(), params).await
+    }
+
+    async fn rename_at(
+        name: &str,
+        line: u32,
+        character: u32,
+        new_name: &str,
+    ) -> Vec<(Url, Range, String)> {
+        let edit = try_rename_at(name, line, character, new_name)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mut edits: Vec<(Url, Range, String)> = edit
+            .changes
+            .unwrap()
+            .into_iter()
+            .flat_map(|(uri, edits)| {
+                edits
+                    .into_iter()
+                    .map(move |edit| (uri.clone(), edit.range, edit.new_text))
+            })
+            .collect();
+        edits.sort_by_key(|(uri, range, _)| (uri.to_string(), range.start));
+        edits
+    }
+
+    fn make_range(line: u32, start: u32, end: u32) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[tokio::test]
+    async fn test_rename_imported_variable() {
+        let new_name = "new_flag".to_string();
+        assert_eq!(
+            rename_at("BUILD.gn", 16, 5, &new_name).await,
+            [
+                (uri("BUILD.gn"), make_range(16, 4, 15), new_name.clone()),
+                (uri("BUILD.gn"), make_range(18, 14, 25), new_name.clone()),
+                (uri("common.gni"), make_range(17, 0, 11), new_name.clone()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_shadowed_variable() {
+        let new_name = "_renamed".to_string();
+        assert_eq!(
+            rename_at("BUILD.gn", 29, 7, &new_name).await,
+            [
+                (uri("BUILD.gn"), make_range(22, 0, 8), new_name.clone()),
+                (uri("BUILD.gn"), make_range(29, 6, 14), new_name.clone()),
+            ]
+        );
+        assert_eq!(
+            rename_at("BUILD.gn", 26, 12, &new_name).await,
+            [
+                (uri("BUILD.gn"), make_range(25, 2, 10), new_name.clone()),
+                (uri("BUILD.gn"), make_range(26, 11, 19), new_name.clone()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_template() {
+        let new_name = "new_template".to_string();
+        assert_eq!(
+            rename_at("BUILD.gn", 17, 4, &new_name).await,
+            [
+                (uri("BUILD.gn"), make_range(17, 2, 13), new_name.clone()),
+                (uri("common.gni"), make_range(19, 10, 21), new_name.clone()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_target() {
+        let new_name = "core".to_string();
+        assert_eq!(
+            rename_at("BUILD.gn", 31, 17, &new_name).await,
+            [
+                (uri("BUILD.gn"), make_range(31, 16, 19), new_name.clone()),
+                (uri("BUILD.gn"), make_range(36, 6, 9), new_name.clone()),
+                (uri("BUILD.gn"), make_range(37, 8, 11), new_name.clone()),
+                (
+                    uri("sub/BUILD.gn"),
+                    make_range(15, 15, 18),
+                    new_name.clone()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_target_with_implicit_name() {
+        assert_eq!(
+            rename_at("sub/BUILD.gn", 14, 8, "subdir").await,
+            [
+                (
+                    uri("BUILD.gn"),
+                    make_range(38, 10, 10),
+                    ":subdir".to_string()
+                ),
+                (
+                    uri("sub/BUILD.gn"),
+                    make_range(14, 7, 10),
+                    "subdir".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_target_to_existing_name() {
+        assert!(try_rename_at("BUILD.gn", 31, 17, "bar").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prepare_rename_forwarded_variable() {
+        let context = test_context();
+        assert!(prepare_rename(&context, position_params("BUILD.gn", 44, 9))
+            .await
+            .is_err());
+        assert!(try_rename_at("BUILD.gn", 44, 9, "new_name").await.is_err());
+
+        // The variable is renamable where it is not forwarded.
+        assert!(prepare_rename(&context, position_params("BUILD.gn", 29, 7))
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
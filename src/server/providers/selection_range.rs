@@ -0,0 +1,153 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pest::Span;
+use tower_lsp::lsp_types::{SelectionRange, SelectionRangeParams};
+
+use crate::{
+    common::{error::Result, utils::OwnedLineIndex},
+    parser::Node,
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+/// Collects spans of the nodes containing the position, from the outermost to
+/// the innermost.
+fn collect_enclosing_spans<'i>(node: &dyn Node<'i>, pos: usize, spans: &mut Vec<Span<'i>>) {
+    spans.push(node.span());
+    if let Some(child) = node
+        .children()
+        .into_iter()
+        .find(|child| child.span().start() <= pos && pos <= child.span().end())
+    {
+        collect_enclosing_spans(child, pos, spans);
+    }
+}
+
+fn compute_selection_range(
+    node: &dyn Node<'_>,
+    pos: usize,
+    line_index: &OwnedLineIndex,
+) -> SelectionRange {
+    let mut spans = Vec::new();
+    collect_enclosing_spans(node, pos, &mut spans);
+
+    let mut ranges = spans.into_iter().map(|span| line_index.range(span));
+    let mut selection = SelectionRange {
+        range: ranges.next().unwrap(),
+        parent: None,
+    };
+    for range in ranges {
+        // Nodes like statements share spans with their only child.
+        if range == selection.range {
+            continue;
+        }
+        selection = SelectionRange {
+            range,
+            parent: Some(Box::new(selection)),
+        };
+    }
+    selection
+}
+
+pub async fn selection_range(
+    context: &RequestContext,
+    params: SelectionRangeParams,
+) -> Result<Option<Vec<SelectionRange>>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let current_file = context.analyzer.analyze_file(&path, context.request_time)?;
+    let line_index = &current_file.document.line_index;
+
+    let mut selections = Vec::new();
+    for position in params.positions {
+        let Some(pos) = line_index.offset(position) else {
+            return Ok(None);
+        };
+        selections.push(compute_selection_range(
+            current_file.parsed_root.get(),
+            pos,
+            line_index,
+        ));
+    }
+
+    Ok(Some(selections))
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use tower_lsp::lsp_types::{Position, Range, TextDocumentIdentifier, Url};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_selection_range() {
+        let selections = selection_range(
+            &RequestContext::new_for_testing(Some(&testdata("workspaces/selection_range"))),
+            SelectionRangeParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(testdata("workspaces/selection_range/BUILD.gn"))
+                        .unwrap(),
+                },
+                positions: vec![Position::new(17, 8)],
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let mut ranges = Vec::new();
+        let mut selection = selections.into_iter().exactly_one().ok();
+        while let Some(current) = selection {
+            ranges.push(current.range);
+            selection = current.parent.map(|parent| *parent);
+        }
+
+        fn make_range(
+            start_line: u32,
+            start_character: u32,
+            end_line: u32,
+            end_character: u32,
+        ) -> Range {
+            Range::new(
+                Position::new(start_line, start_character),
+                Position::new(end_line, end_character),
+            )
+        }
+        assert_eq!(
+            ranges,
+            [
+                // "a.cc"
+                make_range(17, 6, 17, 12),
+                // The list
+                make_range(16, 14, 19, 5),
+                // The assignment
+                make_range(16, 4, 19, 5),
+                // The block of the target
+                make_range(15, 20, 20, 3),
+                // The target
+                make_range(15, 2, 20, 3),
+                // The block of the condition
+                make_range(14, 14, 21, 1),
+                // The condition
+                make_range(14, 0, 21, 1),
+                // The file
+                make_range(0, 0, 22, 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,526 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashSet, sync::Arc};
+
+use either::Either;
+use tower_lsp::lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensLegend,
+    SemanticTokensParams, SemanticTokensRangeParams, SemanticTokensRangeResult,
+    SemanticTokensResult,
+};
+
+use crate::{
+    analyzer::{AnalyzedFile, OwnedEnvironment},
+    common::{builtins::BUILTINS, error::Result, utils::is_exported},
+    parser::{Block, Call, Condition, Expr, Identifier, LValue, Node, PrimaryExpr, Statement},
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::STRING,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+];
+
+const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+    SemanticTokenModifier::new("private"),
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+enum TokenType {
+    Keyword,
+    Comment,
+    String,
+    Number,
+    Operator,
+    Function,
+    Type,
+    Macro,
+    Variable,
+    Property,
+}
+
+const READONLY: u32 = 1 << 0;
+const DEFAULT_LIBRARY: u32 = 1 << 1;
+const PRIVATE: u32 = 1 << 2;
+
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Token {
+    start: usize,
+    end: usize,
+    token_type: TokenType,
+    modifiers: u32,
+}
+
+struct TokenCollector<'a> {
+    file: &'a AnalyzedFile,
+    imports: OwnedEnvironment,
+    args: HashSet<String>,
+    tokens: Vec<Token>,
+}
+
+impl<'a> TokenCollector<'a> {
+    fn new(file: &'a AnalyzedFile, imports: OwnedEnvironment) -> Self {
+        let args = imports
+            .get()
+            .variables
+            .values()
+            .chain(file.exports.get().variables.values())
+            .filter(|variable| variable.is_args)
+            .map(|variable| variable.name.to_string())
+            .collect();
+        Self {
+            file,
+            imports,
+            args,
+            tokens: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, start: usize, end: usize, token_type: TokenType, modifiers: u32) {
+        if start < end {
+            self.tokens.push(Token {
+                start,
+                end,
+                token_type,
+                modifiers,
+            });
+        }
+    }
+
+    fn is_args(&self, ident: &Identifier) -> bool {
+        if !self.args.contains(ident.name) {
+            return false;
+        }
+        // Check the variable is not shadowed by a local one.
+        self.file
+            .local_variables_at(ident.span.start())
+            .get(ident.name)
+            .or_else(|| self.imports.get().variables.get(ident.name))
+            .is_some_and(|variable| variable.is_args)
+    }
+
+    fn collect_comments(&mut self, root: &Block) {
+        let data = self.file.document.data.as_str();
+        let mut string_spans: Vec<(usize, usize)> = root
+            .strings()
+            .map(|string| (string.span.start(), string.span.end()))
+            .collect();
+        string_spans.sort();
+        let mut string_spans = string_spans.into_iter().peekable();
+
+        let mut pos = 0;
+        while let Some(start) = data[pos..].find('#').map(|i| pos + i) {
+            while string_spans.next_if(|&(_, end)| end <= start).is_some() {}
+            if let Some(&(string_start, string_end)) = string_spans.peek() {
+                if string_start <= start {
+                    pos = string_end;
+                    continue;
+                }
+            }
+            let end = data[start..].find('\n').map_or(data.len(), |i| start + i);
+            let content_end = start + data[start..end].trim_end().len();
+            self.push(start, content_end, TokenType::Comment, 0);
+            pos = end;
+        }
+    }
+
+    fn collect_block(&mut self, block: &Block) {
+        for statement in &block.statements {
+            match statement {
+                Statement::Assignment(assignment) => {
+                    match &assignment.lvalue {
+                        LValue::Identifier(identifier) => self.collect_variable(identifier),
+                        LValue::ArrayAccess(array_access) => {
+                            self.collect_variable(&array_access.array);
+                            self.collect_expr(&array_access.index);
+                        }
+                        LValue::ScopeAccess(scope_access) => {
+                            self.collect_variable(&scope_access.scope);
+                            self.collect_member(&scope_access.member);
+                        }
+                    }
+                    self.collect_expr(&assignment.rvalue);
+                }
+                Statement::Call(call) => self.collect_call(call),
+                Statement::Condition(condition) => self.collect_condition(condition),
+                Statement::Error(_) => {}
+            }
+        }
+    }
+
+    fn collect_condition(&mut self, condition: &Condition) {
+        let start = condition.span.start();
+        self.push(start, start + "if".len(), TokenType::Keyword, 0);
+        self.collect_expr(&condition.condition);
+        self.collect_block(&condition.then_block);
+
+        let Some(else_block) = &condition.else_block else {
+            return;
+        };
+        let else_start = match else_block {
+            Either::Left(condition) => condition.span.start(),
+            Either::Right(block) => block.span.start(),
+        };
+
+        // Find the "else" keyword, skipping comments between the blocks.
+        let data = self.file.document.data.as_str();
+        let gap_start = condition.then_block.span.end();
+        let mut line_start = gap_start;
+        for line in data[gap_start..else_start].split_inclusive('\n') {
+            let code = line.split('#').next().unwrap();
+            if let Some(i) = code.find("else") {
+                self.push(
+                    line_start + i,
+                    line_start + i + "else".len(),
+                    TokenType::Keyword,
+                    0,
+                );
+                break;
+            }
+            line_start += line.len();
+        }
+
+        match else_block {
+            Either::Left(condition) => self.collect_condition(condition),
+            Either::Right(block) => self.collect_block(block),
+        }
+    }
+
+    fn collect_call(&mut self, call: &Call) {
+        let name = call.function.name;
+        let (token_type, modifiers) = if BUILTINS.targets.iter().any(|s| s.name == name) {
+            (TokenType::Type, DEFAULT_LIBRARY)
+        } else if BUILTINS.functions.iter().any(|s| s.name == name) {
+            (TokenType::Function, DEFAULT_LIBRARY)
+        } else {
+            (TokenType::Macro, 0)
+        };
+        self.push(
+            call.function.span.start(),
+            call.function.span.end(),
+            token_type,
+            modifiers,
+        );
+        for arg in &call.args {
+            self.collect_expr(arg);
+        }
+        if let Some(block) = &call.block {
+            self.collect_block(block);
+        }
+    }
+
+    fn collect_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Primary(primary_expr) => self.collect_primary_expr(primary_expr),
+            Expr::Unary(unary_expr) => self.collect_expr(&unary_expr.expr),
+            Expr::Binary(binary_expr) => {
+                self.collect_expr(&binary_expr.lhs);
+                self.collect_expr(&binary_expr.rhs);
+            }
+        }
+    }
+
+    fn collect_primary_expr(&mut self, primary_expr: &PrimaryExpr) {
+        match primary_expr {
+            PrimaryExpr::Identifier(identifier) => self.collect_variable(identifier),
+            PrimaryExpr::Integer(integer) => {
+                self.push(
+                    integer.span.start(),
+                    integer.span.end(),
+                    TokenType::Number,
+                    0,
+                );
+            }
+            PrimaryExpr::String(string) => {
+                let data = self.file.document.data.as_str();
+                let mut pos = string.span.start();
+                for expr in &string.embedded_exprs {
+                    let expr_span = expr.span();
+                    // Interpolations are either "$foo" or "${expr}".
+                    let (open, close) = if data[..expr_span.start()].ends_with('$') {
+                        (expr_span.start() - 1, expr_span.end())
+                    } else {
+                        let open = data[pos..expr_span.start()]
+                            .rfind("${")
+                            .map_or(expr_span.start(), |i| pos + i);
+                        let close = data[expr_span.end()..string.span.end()]
+                            .find('}')
+                            .map_or(expr_span.end(), |i| expr_span.end() + i + 1);
+                        (open, close)
+                    };
+                    self.push(pos, open, TokenType::String, 0);
+                    self.push(open, expr_span.start(), TokenType::Operator, 0);
+                    self.collect_expr(expr);
+                    self.push(expr_span.end(), close, TokenType::Operator, 0);
+                    pos = close;
+                }
+                self.push(pos, string.span.end(), TokenType::String, 0);
+            }
+            PrimaryExpr::Call(call) => self.collect_call(call),
+            PrimaryExpr::ArrayAccess(array_access) => {
+                self.collect_variable(&array_access.array);
+                self.collect_expr(&array_access.index);
+            }
+            PrimaryExpr::ScopeAccess(scope_access) => {
+                self.collect_variable(&scope_access.scope);
+                self.collect_member(&scope_access.member);
+            }
+            PrimaryExpr::Block(block) => self.collect_block(block),
+            PrimaryExpr::ParenExpr(paren_expr) => self.collect_expr(&paren_expr.expr),
+            PrimaryExpr::List(list) => {
+                for value in &list.values {
+                    self.collect_expr(value);
+                }
+            }
+            PrimaryExpr::Error(_) => {}
+        }
+    }
+
+    fn collect_variable(&mut self, identifier: &Identifier) {
+        let name = identifier.name;
+        let (token_type, modifiers) = if matches!(name, "true" | "false" | "invoker") {
+            (TokenType::Keyword, 0)
+        } else if BUILTINS
+            .predefined_variables
+            .iter()
+            .chain(BUILTINS.target_variables)
+            .any(|s| s.name == name)
+        {
+            (TokenType::Variable, DEFAULT_LIBRARY)
+        } else if self.is_args(identifier) {
+            (TokenType::Variable, READONLY)
+        } else if !is_exported(name) {
+            (TokenType::Variable, PRIVATE)
+        } else {
+            (TokenType::Variable, 0)
+        };
+        self.push(
+            identifier.span.start(),
+            identifier.span.end(),
+            token_type,
+            modifiers,
+        );
+    }
+
+    fn collect_member(&mut self, identifier: &Identifier) {
+        self.push(
+            identifier.span.start(),
+            identifier.span.end(),
+            TokenType::Property,
+            0,
+        );
+    }
+
+    fn finish(mut self) -> Vec<Token> {
+        self.tokens.sort_by_key(|token| token.start);
+        self.tokens
+    }
+}
+
+fn collect_tokens(file: &AnalyzedFile, imports: OwnedEnvironment) -> Vec<Token> {
+    let root = file.parsed_root.get();
+    let mut collector = TokenCollector::new(file, imports);
+    collector.collect_comments(root);
+    collector.collect_block(root);
+    collector.finish()
+}
+
+fn encode_tokens(file: &AnalyzedFile, tokens: &[Token]) -> Vec<SemanticToken> {
+    let line_index = &file.document.line_index;
+    let mut encoded: Vec<SemanticToken> = Vec::with_capacity(tokens.len());
+    let mut prev_line = 0;
+    let mut prev_character = 0;
+    for token in tokens {
+        let start = line_index.position(token.start);
+        let end = line_index.position(token.end);
+        let delta_line = start.line - prev_line;
+        let delta_start = if delta_line == 0 {
+            start.character - prev_character
+        } else {
+            start.character
+        };
+        encoded.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: end.character - start.character,
+            token_type: token.token_type as u32,
+            token_modifiers_bitset: token.modifiers,
+        });
+        prev_line = start.line;
+        prev_character = start.character;
+    }
+    encoded
+}
+
+fn compute_tokens(context: &RequestContext, file: &Arc<AnalyzedFile>) -> Result<Vec<Token>> {
+    let workspace = context.analyzer.workspace_for(&file.document.path)?;
+    let imports = workspace.analyze_imports(file, context.request_time);
+    Ok(collect_tokens(file, imports))
+}
+
+pub async fn semantic_tokens_full(
+    context: &RequestContext,
+    params: SemanticTokensParams,
+) -> Result<Option<SemanticTokensResult>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let current_file = context.analyzer.analyze_file(&path, context.request_time)?;
+
+    let tokens = compute_tokens(context, &current_file)?;
+
+    Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+        result_id: None,
+        data: encode_tokens(&current_file, &tokens),
+    })))
+}
+
+pub async fn semantic_tokens_range(
+    context: &RequestContext,
+    params: SemanticTokensRangeParams,
+) -> Result<Option<SemanticTokensRangeResult>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let current_file = context.analyzer.analyze_file(&path, context.request_time)?;
+
+    let line_index = &current_file.document.line_index;
+    let (Some(start), Some(end)) = (
+        line_index.offset(params.range.start),
+        line_index.offset(params.range.end),
+    ) else {
+        return Ok(None);
+    };
+
+    let tokens: Vec<Token> = compute_tokens(context, &current_file)?
+        .into_iter()
+        .filter(|token| token.start < end && start < token.end)
+        .collect();
+
+    Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+        result_id: None,
+        data: encode_tokens(&current_file, &tokens),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{TextDocumentIdentifier, Url};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_semantic_tokens() {
+        let path = testdata("workspaces/semantic_tokens/BUILD.gn");
+        let context =
+            RequestContext::new_for_testing(Some(&testdata("workspaces/semantic_tokens")));
+
+        let Some(SemanticTokensResult::Tokens(tokens)) = semantic_tokens_full(
+            &context,
+            SemanticTokensParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap() else {
+            panic!("Unexpected response");
+        };
+
+        // Decode tokens to (text, type, modifiers) for readability.
+        let data = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = data.lines().collect();
+        let mut line = 0;
+        let mut character = 0;
+        let mut decoded: Vec<(String, &str, Vec<&str>)> = Vec::new();
+        for token in tokens.data {
+            if token.delta_line > 0 {
+                line += token.delta_line;
+                character = 0;
+            }
+            character += token.delta_start;
+            // Skip the license header.
+            if line < 14 {
+                continue;
+            }
+            let start = character as usize;
+            let text = lines[line as usize][start..start + token.length as usize].to_string();
+            let modifiers = TOKEN_MODIFIERS
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| token.token_modifiers_bitset & (1 << i) != 0)
+                .map(|(_, modifier)| modifier.as_str())
+                .collect();
+            decoded.push((
+                text,
+                TOKEN_TYPES[token.token_type as usize].as_str(),
+                modifiers,
+            ));
+        }
+
+        let expected = [
+            ("template", "function", vec!["defaultLibrary"]),
+            ("\"my_template\"", "string", vec![]),
+            ("_value", "variable", vec!["private"]),
+            ("invoker", "keyword", vec![]),
+            ("value", "property", vec![]),
+            ("not_needed", "function", vec!["defaultLibrary"]),
+            ("\"_value\"", "string", vec![]),
+            ("if", "keyword", vec![]),
+            ("enable_foo", "variable", vec!["readonly"]),
+            ("my_template", "macro", vec![]),
+            ("\"foo\"", "string", vec![]),
+            ("value", "variable", vec![]),
+            ("\"", "string", vec![]),
+            ("$", "operator", vec![]),
+            ("target_name", "variable", vec!["defaultLibrary"]),
+            (".cc\"", "string", vec![]),
+            ("\"", "string", vec![]),
+            ("${", "operator", vec![]),
+            ("root_out_dir", "variable", vec!["defaultLibrary"]),
+            ("}", "operator", vec![]),
+            ("/x\"", "string", vec![]),
+            ("42", "number", vec![]),
+            ("else", "keyword", vec![]),
+            ("# Comment", "comment", vec![]),
+            ("executable", "type", vec!["defaultLibrary"]),
+            ("\"bar\"", "string", vec![]),
+            ("deps", "variable", vec!["defaultLibrary"]),
+            ("\":foo\"", "string", vec![]),
+            ("# Trailing \"comment\"", "comment", vec![]),
+        ]
+        .map(|(text, token_type, modifiers)| (text.to_string(), token_type, modifiers));
+        assert_eq!(decoded, expected);
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright 2026 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools;
+use tower_lsp::lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp,
+    SignatureHelpParams, SignatureInformation,
+};
+
+use crate::{
+    analyzer::Template,
+    common::{
+        builtins::{BuiltinSymbol, BUILTINS},
+        error::Result,
+    },
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.encode_utf16().count() as u32
+}
+
+/// A call whose argument list contains the cursor.
+struct OpenCall<'i> {
+    name: &'i str,
+    active_parameter: u32,
+}
+
+/// Finds the innermost call whose argument list contains the position. This
+/// works on the raw text rather than the syntax tree, as the call is usually
+/// incomplete while the user is typing arguments.
+fn find_open_call(data: &str, pos: usize) -> Option<OpenCall<'_>> {
+    // Open brackets with the number of commas seen directly inside them.
+    let mut stack: Vec<(usize, char, u32)> = Vec::new();
+    let mut chars = data[..pos].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                let mut escaped = false;
+                for (_, c) in chars.by_ref() {
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '#' => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' | '[' | '{' => stack.push((i, c, 0)),
+            ')' | ']' | '}' => {
+                stack.pop();
+            }
+            ',' => {
+                if let Some((_, _, commas)) = stack.last_mut() {
+                    *commas += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Arguments may contain lists, but not blocks.
+    let (open, _, commas) = stack
+        .into_iter()
+        .rev()
+        .take_while(|(_, bracket, _)| *bracket != '{')
+        .find(|(_, bracket, _)| *bracket == '(')?;
+
+    let prefix = data[..open].trim_end();
+    let name_start = prefix
+        .rfind(|c| !is_identifier_char(c))
+        .map_or(0, |i| i + 1);
+    let name = &prefix[name_start..];
+    if name.is_empty() {
+        return None;
+    }
+    Some(OpenCall {
+        name,
+        active_parameter: commas,
+    })
+}
+
+/// Returns the text between the parentheses of the first call to the builtin
+/// function in its documentation, e.g. `input, new_base = "", ...` for
+/// `rebase_path`.
+fn find_documented_arguments(symbol: &BuiltinSymbol) -> Option<&'static str> {
+    let pattern = format!("{}(", symbol.name);
+    let (start, _) = symbol.doc.match_indices(&pattern).find(|(i, _)| {
+        !symbol.doc[..*i]
+            .chars()
+            .next_back()
+            .is_some_and(is_identifier_char)
+    })?;
+    let start = start + pattern.len();
+
+    let mut depth = 0;
+    let mut in_string = false;
+    for (i, c) in symbol.doc[start..].char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(&symbol.doc[start..start + i]),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits documented arguments into parameter names. Optional parameters are
+/// documented like `assert(<condition> [, <error string>])`, so unbalanced
+/// square brackets are dropped.
+fn split_documented_arguments(arguments: &str) -> Vec<String> {
+    let mut parameters: Vec<String> = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut current = String::new();
+    for c in arguments.chars() {
+        match c {
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parameters.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parameters.push(current);
+
+    parameters
+        .into_iter()
+        .map(|parameter| {
+            let mut parameter = parameter.split_whitespace().join(" ");
+            let opens = parameter.matches('[').count();
+            let closes = parameter.matches(']').count();
+            if opens > closes {
+                parameter = parameter
+                    .trim_start_matches(['[', ' '])
+                    .trim_end_matches(['[', ' '])
+                    .to_string();
+            } else if closes > opens {
+                parameter = parameter.trim_end_matches([']', ' ']).to_string();
+            }
+            parameter
+        })
+        .filter(|parameter| !parameter.is_empty())
+        .collect()
+}
+
+fn make_signature(
+    name: &str,
+    parameters: &[String],
+    documentation: Option<String>,
+) -> SignatureInformation {
+    let mut label = format!("{name}(");
+    let mut parameter_infos = Vec::new();
+    for (i, parameter) in parameters.iter().enumerate() {
+        if i > 0 {
+            label.push_str(", ");
+        }
+        let start = utf16_len(&label);
+        label.push_str(parameter);
+        parameter_infos.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, utf16_len(&label)]),
+            documentation: None,
+        });
+    }
+    label.push(')');
+
+    SignatureInformation {
+        label,
+        documentation: documentation.map(|value| {
+            Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            })
+        }),
+        parameters: Some(parameter_infos),
+        active_parameter: None,
+    }
+}
+
+fn builtin_signature(symbol: &BuiltinSymbol) -> Option<SignatureInformation> {
+    let parameters = split_documented_arguments(find_documented_arguments(symbol)?);
+    Some(make_signature(
+        symbol.name,
+        &parameters,
+        Some(symbol.doc.to_string()),
+    ))
+}
+
+fn template_signature(template: &Template) -> SignatureInformation {
+    let documentation = (!template.comments.is_empty())
+        .then(|| format!("```text\n{}\n```", template.comments.to_string().trim()));
+    make_signature(template.name, &["target_name".to_string()], documentation)
+}
+
+pub async fn signature_help(
+    context: &RequestContext,
+    params: SignatureHelpParams,
+) -> Result<Option<SignatureHelp>> {
+    let path = get_text_document_path(&params.text_document_position_params.text_document)?;
+    let current_file = context.analyzer.analyze_file(&path, context.request_time)?;
+
+    let Some(pos) = current_file
+        .document
+        .line_index
+        .offset(params.text_document_position_params.position)
+    else {
+        return Ok(None);
+    };
+
+    let Some(call) = find_open_call(&current_file.document.data, pos) else {
+        return Ok(None);
+    };
+
+    let environment = context
+        .analyzer
+        .analyze_at(&current_file, pos, context.request_time)?;
+
+    let signature = if let Some(template) = environment.get().templates.get(call.name) {
+        template_signature(template)
+    } else if let Some(symbol) = BUILTINS
+        .functions
+        .iter()
+        .find(|symbol| symbol.name == call.name)
+    {
+        let Some(signature) = builtin_signature(symbol) else {
+            return Ok(None);
+        };
+        signature
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(SignatureHelp {
+        signatures: vec![signature],
+        active_signature: Some(0),
+        active_parameter: Some(call.active_parameter),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{Position, TextDocumentIdentifier, TextDocumentPositionParams, Url};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    async fn signature_help_at(line: u32, character: u32) -> Option<SignatureHelp> {
+        signature_help(
+            &RequestContext::new_for_testing(Some(&testdata("workspaces/signature_help"))),
+            SignatureHelpParams {
+                context: None,
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(testdata("workspaces/signature_help/BUILD.gn"))
+                            .unwrap(),
+                    },
+                    position: Position::new(line, character),
+                },
+                work_done_progress_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    fn parameter_labels(signature: &SignatureInformation) -> Vec<&str> {
+        let label: Vec<u16> = signature.label.encode_utf16().collect();
+        signature
+            .parameters
+            .iter()
+            .flatten()
+            .map(|parameter| {
+                let ParameterLabel::LabelOffsets([start, end]) = parameter.label else {
+                    panic!("unexpected label: {:?}", parameter.label);
+                };
+                let prefix = String::from_utf16(&label[..start as usize]).unwrap();
+                let len = (end - start) as usize;
+                &signature.label[prefix.len()..prefix.len() + len]
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_signature_help_builtin() {
+        let help = signature_help_at(21, 52).await.unwrap();
+        let signature = &help.signatures[0];
+        assert_eq!(
+            signature.label,
+            r#"rebase_path(input, new_base = "", current_base = ".")"#
+        );
+        assert_eq!(
+            parameter_labels(signature),
+            ["input", r#"new_base = """#, r#"current_base = ".""#]
+        );
+        assert_eq!(help.active_parameter, Some(2));
+
+        let help = signature_help_at(21, 44).await.unwrap();
+        assert_eq!(
+            help.signatures[0].label,
+            "get_label_info(target_label, what)"
+        );
+        assert_eq!(help.active_parameter, Some(1));
+
+        // Not directly inside an argument list.
+        assert_eq!(signature_help_at(14, 0).await, None);
+        assert_eq!(signature_help_at(18, 2).await, None);
+        assert_eq!(signature_help_at(19, 1).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_signature_help_template() {
+        let help = signature_help_at(22, 7).await.unwrap();
+        let signature = &help.signatures[0];
+        assert_eq!(signature.label, "widget(target_name)");
+        assert_eq!(
+            signature.documentation,
+            Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "```text\nBuilds a widget.\n```".to_string(),
+            }))
+        );
+        assert_eq!(help.active_parameter, Some(0));
+    }
+
+    #[test]
+    fn test_split_documented_arguments() {
+        assert_eq!(
+            split_documented_arguments("<condition> [, <error string>]"),
+            ["<condition>", "<error string>"]
+        );
+        assert_eq!(
+            split_documented_arguments(
+                "from_scope, variable_list_or_star,\n    variable_to_not_forward_list = []"
+            ),
+            [
+                "from_scope",
+                "variable_list_or_star",
+                "variable_to_not_forward_list = []"
+            ]
+        );
+    }
+}
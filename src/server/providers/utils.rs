@@ -20,7 +20,7 @@ use tower_lsp::lsp_types::TextDocumentIdentifier;
 use crate::{
     analyzer::{AnalyzedFile, Target},
     common::error::{Error, Result},
-    parser::{Identifier, Node},
+    parser::{Identifier, Node, ScopeAccess},
 };
 
 pub fn get_text_document_path(text_document: &TextDocumentIdentifier) -> Result<PathBuf> {
@@ -37,6 +37,17 @@ pub fn lookup_identifier_at(file: &AnalyzedFile, pos: usize) -> Option<&Identifi
         .find(|ident| ident.span.start() <= pos && pos <= ident.span.end())
 }
 
+/// Returns the scope access whose member is at the position.
+pub fn lookup_scope_member_at(file: &AnalyzedFile, pos: usize) -> Option<&ScopeAccess<'_>> {
+    file.parsed_root
+        .get()
+        .walk()
+        .filter_map(|node| node.as_scope_access())
+        .find(|scope_access| {
+            scope_access.member.span.start() <= pos && pos <= scope_access.member.span.end()
+        })
+}
+
 pub fn lookup_target_name_string_at(file: &AnalyzedFile, pos: usize) -> Option<Target<'_>> {
     file.analyzed_root.get().targets().find(|target| {
         target.call.args[0].span().start() <= pos && pos <= target.call.args[0].span().end()
@@ -190,6 +190,39 @@ async function chooseImportCandidates(
   }
 }
 
+interface BuildDirsResult {
+  buildDirs: string[];
+  activeBuildDir: string | null;
+}
+
+async function selectBuildDir(client: LanguageClient): Promise<void> {
+  const document = vscode.window.activeTextEditor?.document;
+  if (!document) {
+    void vscode.window.showErrorMessage('No open editor.');
+    return;
+  }
+
+  const textDocument = {uri: document.uri.toString()};
+  const result: BuildDirsResult = await client.sendRequest('gn/buildDirs', {
+    textDocument,
+  });
+  const items = [
+    {label: 'None', description: 'Ignore args.gn', buildDir: null},
+    ...result.buildDirs.map(buildDir => ({
+      label: vscode.workspace.asRelativePath(buildDir),
+      description: buildDir === result.activeBuildDir ? 'active' : undefined,
+      buildDir,
+    })),
+  ];
+  const selectedItem = await vscode.window.showQuickPick(items);
+  if (selectedItem) {
+    await client.sendRequest('gn/setActiveBuildDir', {
+      textDocument,
+      buildDir: selectedItem.buildDir,
+    });
+  }
+}
+
 class GnLanguageClient extends LanguageClient {
   constructor(context: vscode.ExtensionContext, output: vscode.OutputChannel) {
     const clientOptions: LanguageClientOptions = {
@@ -265,6 +298,9 @@ async function startLanguageServer(
     ),
     vscode.commands.registerCommand('gn.chooseImportCandidates', data =>
       chooseImportCandidates(data, client.protocol2CodeConverter)
+    ),
+    vscode.commands.registerCommand('gn.selectBuildDir', () =>
+      selectBuildDir(client)
     )
   );
 }